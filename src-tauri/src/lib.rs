@@ -1,8 +1,8 @@
 mod minecraft;
 
-use minecraft::{versions, downloader, instances, launcher, settings, auth, modrinth, files, fabric, forge, java, logger};
+use minecraft::{versions, downloader, instances, launcher, settings, auth, modrinth, files, fabric, quilt, forge, java, logger, backups, regions, server_status};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::sync::{Mutex, LazyLock};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -16,6 +16,8 @@ pub struct RunningProcessInfo {
 }
 
 static RUNNING_PROCESSES: LazyLock<Mutex<HashMap<String, RunningProcessInfo>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+/// Instances whose game the user stopped, read by the exit thread to record the session as killed
+static USER_KILLED: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
 static BOOTSTRAP_START: LazyLock<std::time::Instant> = LazyLock::new(std::time::Instant::now);
 
 // App state for storing user info
@@ -60,6 +62,12 @@ pub struct GlobalStats {
     pub instance_count: u32,
     pub most_played_instance: Option<String>,
     pub favorite_version: Option<String>,
+    pub total_sessions: u32,
+    pub average_session_seconds: u64,
+    pub longest_session_seconds: u64,
+    pub crash_count: u32,
+    pub current_streak_days: u32,
+    pub longest_streak_days: u32,
 }
 
 #[tauri::command]
@@ -93,9 +101,55 @@ fn get_global_stats() -> Result<GlobalStats, String> {
         .max_by_key(|&(_, count)| count)
         .map(|(version, _)| version);
     
+    // Session history
+    let summary = minecraft::history::summary();
+    stats.total_sessions = summary.total_sessions;
+    stats.average_session_seconds = summary.average_session_seconds;
+    stats.longest_session_seconds = summary.longest_session_seconds;
+    stats.crash_count = summary.crashes;
+    let streaks = minecraft::history::streaks(None);
+    stats.current_streak_days = streaks.current_streak_days;
+    stats.longest_streak_days = streaks.longest_streak_days;
+    
     Ok(stats)
 }
 
+#[tauri::command]
+fn get_session_history(instance_id: Option<String>, limit: Option<usize>) -> Vec<minecraft::history::PlaySession> {
+    minecraft::history::get_history(instance_id.as_deref(), limit)
+}
+
+#[tauri::command]
+fn get_playtime_by_day(days: Option<u32>, instance_id: Option<String>) -> Vec<minecraft::history::PlaytimeBucket> {
+    minecraft::history::playtime_by_day(days.unwrap_or(30), instance_id.as_deref())
+}
+
+#[tauri::command]
+fn get_playtime_by_week(weeks: Option<u32>, instance_id: Option<String>) -> Vec<minecraft::history::PlaytimeBucket> {
+    minecraft::history::playtime_by_week(weeks.unwrap_or(12), instance_id.as_deref())
+}
+
+#[tauri::command]
+fn get_instance_session_stats() -> Vec<minecraft::history::InstanceSessionStats> {
+    minecraft::history::instance_stats()
+}
+
+#[tauri::command]
+fn get_crash_stats() -> Vec<minecraft::history::VersionCrashStats> {
+    minecraft::history::crash_stats()
+}
+
+#[tauri::command]
+fn get_play_streaks(instance_id: Option<String>) -> minecraft::history::StreakStats {
+    minecraft::history::streaks(instance_id.as_deref())
+}
+
+/// Export the play history as "csv" or "json"
+#[tauri::command]
+fn export_session_history(format: String, destination_path: String, instance_id: Option<String>) -> Result<usize, String> {
+    minecraft::history::export_history(&format, std::path::Path::new(&destination_path), instance_id.as_deref())
+}
+
 #[tauri::command]
 fn get_bootstrap_time() -> f64 {
     BOOTSTRAP_START.elapsed().as_secs_f64()
@@ -178,9 +232,12 @@ async fn clone_instance(instance_id: String, new_name: String, app_handle: AppHa
     
     // 3. Copy settings from source to cloned
     cloned.java_path = source.java_path.clone();
+    cloned.java_runtime = source.java_runtime.clone();
     cloned.jvm_args = source.jvm_args.clone();
     cloned.memory_min = source.memory_min;
     cloned.memory_max = source.memory_max;
+    cloned.jvm_profile = source.jvm_profile;
+    cloned.auto_memory = source.auto_memory;
     cloned.resolution_width = source.resolution_width;
     cloned.resolution_height = source.resolution_height;
     cloned.mod_loader = source.mod_loader.clone();
@@ -188,6 +245,13 @@ async fn clone_instance(instance_id: String, new_name: String, app_handle: AppHa
     cloned.console_auto_update = source.console_auto_update;
     cloned.logo_filename = source.logo_filename.clone();
     cloned.color_accent = source.color_accent.clone();
+    cloned.backup_worlds_on_exit = source.backup_worlds_on_exit;
+    cloned.backup_retention = source.backup_retention.clone();
+    cloned.backup_retention_count = source.backup_retention_count;
+    cloned.wrapper_command = source.wrapper_command.clone();
+    cloned.env_vars = source.env_vars.clone();
+    cloned.pre_launch_command = source.pre_launch_command.clone();
+    cloned.post_exit_command = source.post_exit_command.clone();
     
     // Update the saved metadata
     instances::update_instance(cloned.clone())?;
@@ -489,6 +553,8 @@ async fn export_instance_zip(instance_id: String, destination_path: String, app_
         "memory_min": instance.memory_min,
         "memory_max": instance.memory_max,
         "jvm_args": instance.jvm_args,
+        "jvm_profile": instance.jvm_profile,
+        "auto_memory": instance.auto_memory,
         "resolution_width": instance.resolution_width,
         "resolution_height": instance.resolution_height,
         "color_accent": instance.color_accent,
@@ -628,6 +694,7 @@ async fn import_instance_zip(zip_path: String, custom_name: Option<String>, app_
         "Fabric" => instances::ModLoader::Fabric,
         "Forge" => instances::ModLoader::Forge,
         "NeoForge" => instances::ModLoader::NeoForge,
+        "Quilt" => instances::ModLoader::Quilt,
         _ => instances::ModLoader::Vanilla,
     };
     new_instance.mod_loader_version = mod_loader_version;
@@ -642,6 +709,12 @@ async fn import_instance_zip(zip_path: String, custom_name: Option<String>, app_
     if let Some(jvm_args) = metadata["jvm_args"].as_str() {
         new_instance.jvm_args = Some(jvm_args.to_string());
     }
+    if let Ok(profile) = serde_json::from_value(metadata["jvm_profile"].clone()) {
+        new_instance.jvm_profile = profile;
+    }
+    if let Some(auto_memory) = metadata["auto_memory"].as_bool() {
+        new_instance.auto_memory = auto_memory;
+    }
     if let Some(width) = metadata["resolution_width"].as_u64() {
         new_instance.resolution_width = Some(width as u32);
     }
@@ -730,16 +803,22 @@ async fn import_instance_zip(zip_path: String, custom_name: Option<String>, app_
             }
             instances::ModLoader::Forge => {
                 logger::emit_log(&app_handle, "info", &format!("Installing Forge {} for imported instance", loader_version_clone));
-                forge::install_forge(&new_instance, &loader_version_clone)
+                forge::install_forge(&new_instance, &loader_version_clone, Some(&app_handle))
                     .await
                     .map_err(|e| format!("Failed to install Forge: {}", e))?;
             }
             instances::ModLoader::NeoForge => {
                 logger::emit_log(&app_handle, "info", &format!("Installing NeoForge {} for imported instance", loader_version_clone));
-                forge::install_neoforge(&new_instance, &loader_version_clone)
+                forge::install_neoforge(&new_instance, &loader_version_clone, Some(&app_handle))
                     .await
                     .map_err(|e| format!("Failed to install NeoForge: {}", e))?;
             }
+            instances::ModLoader::Quilt => {
+                logger::emit_log(&app_handle, "info", &format!("Installing Quilt {} for imported instance", loader_version_clone));
+                quilt::install_quilt(&new_instance, &loader_version_clone)
+                    .await
+                    .map_err(|e| format!("Failed to install Quilt: {}", e))?;
+            }
             instances::ModLoader::Vanilla => {
                 // No mod loader to install
             }
@@ -857,6 +936,7 @@ fn get_instance_share_code(instance_id: String) -> Result<String, String> {
             instances::ModLoader::Fabric => "fabric".to_string(),
             instances::ModLoader::Forge => "forge".to_string(),
             instances::ModLoader::NeoForge => "neoforge".to_string(),
+            instances::ModLoader::Quilt => "quilt".to_string(),
         },
         loader_version: instance.mod_loader_version,
         mods,
@@ -900,6 +980,7 @@ fn get_instance_mods_share_code(instance_id: String) -> Result<String, String> {
             instances::ModLoader::Fabric => "fabric".to_string(),
             instances::ModLoader::Forge => "forge".to_string(),
             instances::ModLoader::NeoForge => "neoforge".to_string(),
+            instances::ModLoader::Quilt => "quilt".to_string(),
         },
         loader_version: instance.mod_loader_version,
         mods,
@@ -944,10 +1025,10 @@ async fn install_fabric(instance_id: String, loader_version: String) -> Result<S
 }
 
 #[tauri::command]
-async fn install_forge(instance_id: String, loader_version: String) -> Result<String, String> {
+async fn install_forge(instance_id: String, loader_version: String, app_handle: AppHandle) -> Result<String, String> {
     let instance = instances::get_instance(&instance_id)?;
     
-    forge::install_forge(&instance, &loader_version)
+    forge::install_forge(&instance, &loader_version, Some(&app_handle))
         .await
         .map_err(|e| e.to_string())?;
     
@@ -961,10 +1042,10 @@ async fn install_forge(instance_id: String, loader_version: String) -> Result<St
 }
 
 #[tauri::command]
-async fn install_neoforge(instance_id: String, loader_version: String) -> Result<String, String> {
+async fn install_neoforge(instance_id: String, loader_version: String, app_handle: AppHandle) -> Result<String, String> {
     let instance = instances::get_instance(&instance_id)?;
     
-    forge::install_neoforge(&instance, &loader_version)
+    forge::install_neoforge(&instance, &loader_version, Some(&app_handle))
         .await
         .map_err(|e| e.to_string())?;
     
@@ -977,6 +1058,23 @@ async fn install_neoforge(instance_id: String, loader_version: String) -> Result
     Ok(format!("NeoForge {} installed successfully", loader_version))
 }
 
+#[tauri::command]
+async fn install_quilt(instance_id: String, loader_version: String) -> Result<String, String> {
+    let instance = instances::get_instance(&instance_id)?;
+    
+    quilt::install_quilt(&instance, &loader_version)
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    // Update instance with mod loader info
+    let mut updated = instance.clone();
+    updated.mod_loader = instances::ModLoader::Quilt;
+    updated.mod_loader_version = Some(loader_version.clone());
+    instances::update_instance(updated)?;
+    
+    Ok(format!("Quilt {} installed successfully", loader_version))
+}
+
 // ============== DOWNLOAD COMMANDS ==============
 
 #[tauri::command]
@@ -997,9 +1095,53 @@ fn is_version_downloaded(version_id: String) -> bool {
 
 // ============== LAUNCH COMMANDS ==============
 
+/// Build the launch plan for an instance with the current account, without starting it.
+/// A dry run reports missing files instead of downloading them.
+async fn plan_launch(instance_id: &str, dry_run: bool, state: &State<'_, AppState>, app_handle: &AppHandle) -> Result<(launcher::LaunchPlan, String), String> {
+    let instance = instances::get_instance(instance_id)?;
+    let version_details = versions::resolve_version(&instance.version_id)?;
+
+    let username = state.username.lock().map_err(|_| "Auth state corrupted")?.clone();
+    let uuid = state.uuid.lock().map_err(|_| "Auth state corrupted")?.clone();
+    let access_token = state.access_token.lock().map_err(|_| "Auth state corrupted")?.clone();
+
+    let plan = launcher::prepare_launch(&instance, &version_details, &username, &access_token, &uuid, None, dry_run, app_handle).await?;
+    Ok((plan, access_token))
+}
+
+/// Show exactly how an instance would be launched (Java, classpath, arguments) with secrets redacted
+#[tauri::command]
+async fn preview_launch(
+    instance_id: String,
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<launcher::LaunchPreview, String> {
+    let (plan, access_token) = plan_launch(&instance_id, true, &state, &app_handle).await?;
+    Ok(launcher::preview_from_plan(&plan, &access_token))
+}
+
+/// Export a launch.sh into the instance folder that starts the game outside the launcher
+#[tauri::command]
+async fn export_launch_script(
+    instance_id: String,
+    token_placeholder: Option<bool>,
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    let instance = instances::get_instance(&instance_id)?;
+    // The script has to run on its own, so the files it needs are downloaded first
+    let (plan, access_token) = plan_launch(&instance_id, false, &state, &app_handle).await?;
+
+    let script_path = instance.get_directory().join("launch.sh");
+    launcher::export_launch_script(&plan, &access_token, token_placeholder.unwrap_or(true), &script_path)?;
+    log_info!(&app_handle, "Exported launch script for {} to {}", instance.name, script_path.display());
+    Ok(script_path.to_string_lossy().to_string())
+}
+
 #[tauri::command]
 async fn launch_instance(
     instance_id: String,
+    quick_play: Option<launcher::QuickPlayTarget>,
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<String, String> {
@@ -1024,13 +1166,29 @@ async fn launch_instance(
         total: 0,
     });
     
-    // Check if version is downloaded
-    let versions_dir = downloader::get_versions_dir();
-    let json_path = versions_dir.join(&instance.version_id).join(format!("{}.json", &instance.version_id));
-    let json_content = std::fs::read_to_string(&json_path)
-        .map_err(|e| format!("Failed to read version JSON: {}", e))?;
-    let version_details: versions::VersionDetails = serde_json::from_str(&json_content)
-        .map_err(|e| format!("Failed to parse version JSON: {}", e))?;
+    // Check if version is downloaded, custom versions (e.g. OptiFine) are resolved through inheritsFrom
+    let version_details = versions::resolve_version(&instance.version_id)?;
+
+    // Validate the Quick Play target before spending time on the launch
+    match &quick_play {
+        Some(launcher::QuickPlayTarget::World { folder_name }) => {
+            if !files::get_saves_dir(&instance).join(folder_name).join("level.dat").exists() {
+                return Err(format!("World '{}' not found in this instance", folder_name));
+            }
+            if !launcher::supports_quick_play(&version_details) {
+                return Err("Launching straight into a world needs Minecraft 1.20 or newer".to_string());
+            }
+        }
+        Some(launcher::QuickPlayTarget::Realm { .. }) => {
+            if !launcher::supports_quick_play(&version_details) {
+                return Err("Launching straight into a realm needs Minecraft 1.20 or newer".to_string());
+            }
+        }
+        Some(launcher::QuickPlayTarget::Server { address }) => {
+            minecraft::ping::parse_address(address)?;
+        }
+        None => {}
+    }
     
     let username = state.username.lock().map_err(|_| "Auth state corrupted")?.clone();
     let uuid = state.uuid.lock().map_err(|_| "Auth state corrupted")?.clone();
@@ -1056,14 +1214,19 @@ async fn launch_instance(
     });
     
     // Launch the game
-    let mut child = launcher::launch_game(&instance, &version_details, &username, &access_token, &uuid, &app_handle).await?;
+    let (mut child, java_path, env_vars) = launcher::launch_game(&instance, &version_details, &username, &access_token, &uuid, quick_play.as_ref(), &app_handle).await?;
     
     // Store the process ID
     let process_id = child.id();
     
     // Write session file for crash recovery (include PID)
-    let _ = instances::write_active_session(&instance_id, start_time, Some(process_id));
+    let _ = instances::write_active_session(&instance, start_time, Some(process_id));
     
+    // A kill that raced the previous session's exit must not carry over to this one. Cleared
+    // before the game is registered, so a kill_game for this session can't be wiped out
+    if let Ok(mut killed) = USER_KILLED.lock() {
+        killed.remove(&instance_id);
+    }
     {
         let mut processes = RUNNING_PROCESSES.lock().map_err(|_| "Process state corrupted")?;
         processes.insert(instance_id.clone(), RunningProcessInfo {
@@ -1076,6 +1239,9 @@ async fn launch_instance(
     let instance_id_clone = instance_id.clone();
     let app_handle_clone = app_handle.clone();
     let instance_name = instance.name.clone();
+    // Recorded as the instance was launched, in case it changes while the game runs
+    let mut play_session = minecraft::history::PlaySession::new(&instance, start_time, start_time, minecraft::jvm::count_mods(&instance));
+    play_session.account = Some(username.clone()).filter(|u| !u.is_empty());
     std::thread::spawn(move || {
         // Wait for the game to exit
         if let Ok(status) = child.wait() {
@@ -1092,15 +1258,54 @@ async fn launch_instance(
             }
             
             // Clear the session file since we exited normally
-            instances::clear_active_session();
+            instances::clear_active_session(&instance_id_clone);
             
             // Remove from running processes
             if let Ok(mut processes) = RUNNING_PROCESSES.lock() {
                 processes.remove(&instance_id_clone);
             }
+            let killed = USER_KILLED.lock()
+                .map(|mut killed| killed.remove(&instance_id_clone))
+                .unwrap_or(false);
+            
+            // Add the session to the play history
+            play_session.end_time = end_time;
+            play_session.duration_seconds = session_duration;
+            play_session.exit_code = status.code();
+            play_session.killed = killed;
+            play_session.crashed = !killed && !status.success();
+            if let Err(e) = minecraft::history::record_session(&play_session) {
+                log_warn!(&app_handle_clone, "{}", e);
+            }
             
             log_info!(&app_handle_clone, "Instance {} exited with status: {:?}, session duration: {}s", instance_name, status, session_duration);
             log::info!("Game exited with status: {:?}, session: {}s", status, session_duration);
+            
+            if let Ok(inst) = instances::get_instance(&instance_id_clone) {
+                // Post-exit hook on its own thread so a slow or hanging command doesn't hold up
+                // the backups, failures are only logged since the game is already gone
+                let launch_hooks = minecraft::hooks::resolve_hooks(&inst);
+                if let Some(post_exit) = launch_hooks.post_exit_command {
+                    let hook_instance = inst.clone();
+                    let hook_java = java_path.clone();
+                    let hook_app_handle = app_handle_clone.clone();
+                    std::thread::spawn(move || {
+                        if let Err(e) = minecraft::hooks::run_hook("post-exit", &post_exit, &hook_instance, &hook_java, &env_vars) {
+                            log_warn!(&hook_app_handle, "{}", e);
+                        }
+                    });
+                }
+
+                // Back up worlds played this session if the instance asks for it
+                if inst.backup_worlds_on_exit {
+                    for result in backups::backup_worlds_after_session(&inst, start_time) {
+                        match result {
+                            Ok(backup) => log_info!(&app_handle_clone, "Backed up world {} ({})", backup.level_name, backup.filename),
+                            Err(e) => log_warn!(&app_handle_clone, "World backup failed for {}: {}", instance_name, e),
+                        }
+                    }
+                }
+            }
         }
     });
     
@@ -1119,20 +1324,23 @@ fn kill_game(
     match process_info {
         Some(info) => {
             let pid = info.pid;
+            // Marked before the kill so the exit thread sees it however quickly the game goes down
+            USER_KILLED.lock().map_err(|_| "Process state corrupted")?.insert(instance_id.clone());
+
             // Kill the process using system commands
             #[cfg(target_os = "windows")]
-            {
-                std::process::Command::new("taskkill")
-                    .args(&["/F", "/PID", &pid.to_string()])
-                    .spawn()
-                    .map_err(|e| format!("Failed to kill process: {}", e))?;
-            }
+            let spawned = std::process::Command::new("taskkill")
+                .args(&["/F", "/PID", &pid.to_string()])
+                .spawn();
             #[cfg(not(target_os = "windows"))]
-            {
-                std::process::Command::new("kill")
-                    .args(&["-9", &pid.to_string()])
-                    .spawn()
-                    .map_err(|e| format!("Failed to kill process: {}", e))?;
+            let spawned = std::process::Command::new("kill")
+                .args(&["-9", &pid.to_string()])
+                .spawn();
+            if let Err(e) = spawned {
+                if let Ok(mut killed) = USER_KILLED.lock() {
+                    killed.remove(&instance_id);
+                }
+                return Err(format!("Failed to kill process: {}", e));
             }
             
             // Remove from running processes
@@ -1142,7 +1350,7 @@ fn kill_game(
             }
             
             // Clear session file
-            instances::clear_active_session();
+            instances::clear_active_session(&instance_id);
             
             Ok(format!("Killed game for instance {}", instance_id))
         }
@@ -1196,9 +1404,9 @@ fn save_settings(new_settings: settings::LauncherSettings) -> Result<(), String>
 }
 
 #[tauri::command]
-async fn download_java_for_instance(instance_id: String, version: u32) -> Result<instances::Instance, String> {
+async fn download_java_for_instance(instance_id: String, version: u32, app_handle: AppHandle) -> Result<instances::Instance, String> {
     let instance = instances::get_instance(&instance_id)?;
-    let java_path = java::download_java(version)
+    let java_path = java::download_java(version, Some(&app_handle))
         .await
         .map_err(|e| e.to_string())?;
 
@@ -1208,8 +1416,8 @@ async fn download_java_for_instance(instance_id: String, version: u32) -> Result
 }
 
 #[tauri::command]
-async fn download_java_global(version: u32) -> Result<String, String> {
-    let java_path = java::download_java(version)
+async fn download_java_global(version: u32, app_handle: AppHandle) -> Result<String, String> {
+    let java_path = java::download_java(version, Some(&app_handle))
         .await
         .map_err(|e| e.to_string())?;
     Ok(java_path.to_string_lossy().to_string())
@@ -1222,6 +1430,60 @@ async fn is_java_version_installed(version: u32) -> Result<bool, String> {
     Ok(install_dir.exists())
 }
 
+#[tauri::command]
+async fn get_memory_recommendation(instance_id: String) -> Result<minecraft::jvm::MemoryRecommendation, String> {
+    let instance = instances::get_instance(&instance_id)?;
+    tokio::task::spawn_blocking(move || minecraft::jvm::recommend_memory(&instance))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn list_java_runtimes() -> Result<Vec<java::JavaRuntime>, String> {
+    tokio::task::spawn_blocking(java::list_runtimes)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Install a runtime, source is "mojang" with a component name (java-runtime-gamma)
+/// or "temurin" with a major version
+#[tauri::command]
+async fn install_java_runtime(source: String, name: String, app_handle: AppHandle) -> Result<java::JavaRuntime, String> {
+    let runtime_id = match source.as_str() {
+        "mojang" => {
+            java::install_mojang_runtime(&name, Some(&app_handle))
+                .await
+                .map_err(|e| e.to_string())?;
+            name
+        }
+        "temurin" => {
+            let version: u32 = name.parse().map_err(|_| format!("Invalid Java version: {}", name))?;
+            java::download_java(version, Some(&app_handle))
+                .await
+                .map_err(|e| e.to_string())?;
+            format!("temurin-{}", version)
+        }
+        other => return Err(format!("Unknown Java runtime source: {}", other)),
+    };
+
+    tokio::task::spawn_blocking(move || {
+        java::get_managed_runtime(&runtime_id)
+            .ok_or_else(|| format!("Java runtime {} was not installed", runtime_id))
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+fn remove_java_runtime(runtime_id: String) -> Result<(), String> {
+    java::remove_runtime(&runtime_id)
+}
+
+#[tauri::command]
+async fn validate_java_runtime(runtime_id: String) -> Result<java::JavaValidation, String> {
+    tokio::task::spawn_blocking(move || java::validate_runtime(&runtime_id))
+        .await
+        .map_err(|e| e.to_string())
+}
+
 // ----------
 // GitHub Release Info
 // Description: Struct to hold release information from GitHub API
@@ -1866,6 +2128,7 @@ async fn get_downloaded_versions() -> Result<Vec<DownloadedVersion>, String> {
                             instances::ModLoader::Fabric => "Fabric",
                             instances::ModLoader::Forge => "Forge",
                             instances::ModLoader::NeoForge => "NeoForge",
+                            instances::ModLoader::Quilt => "Quilt",
                             _ => "ModLoader",
                         };
                         
@@ -1890,6 +2153,15 @@ async fn get_downloaded_versions() -> Result<Vec<DownloadedVersion>, String> {
                                         size += m.len();
                                     }
                                 }
+                            } else if inst.mod_loader == instances::ModLoader::Quilt {
+                                if let Some(quilt_info) = quilt::load_quilt_info(&inst) {
+                                    let lib_dir = downloader::get_libraries_dir();
+                                    for jar in [&quilt_info.loader.loader.maven, &quilt_info.loader.hashed.maven] {
+                                        if let Ok(m) = fs::metadata(lib_dir.join(fabric::maven_to_path(jar))) {
+                                            size += m.len();
+                                        }
+                                    }
+                                }
                             }
 
                             versions.push(DownloadedVersion {
@@ -1912,7 +2184,7 @@ async fn get_downloaded_versions() -> Result<Vec<DownloadedVersion>, String> {
 #[tauri::command]
 fn delete_version(version_id: String) -> Result<String, String> {
     // Check if this is a synthesized mod loader version
-    if version_id.contains("Fabric") || version_id.contains("Forge") || version_id.contains("NeoForge") {
+    if version_id.contains("Fabric") || version_id.contains("Forge") || version_id.contains("NeoForge") || version_id.contains("Quilt") {
         return Err("Mod loader versions cannot be deleted from here. Modify or delete the instance that uses it instead.".to_string());
     }
 
@@ -1929,6 +2201,7 @@ fn delete_version(version_id: String) -> Result<String, String> {
         fs::remove_dir_all(&version_dir)
             .map_err(|e| format!("Failed to delete version index: {}", e))?;
     }
+    minecraft::natives::delete_natives_cache(&version_id)?;
     
     Ok(format!("Deleted version index for {}", version_id))
 }
@@ -2002,6 +2275,58 @@ async fn get_loader_versions(loader: String, game_version: String) -> Result<Vec
                 }
             }).collect())
         }
+        "quilt" => {
+            // Fetch Quilt loader versions from Quilt meta
+            let client = reqwest::Client::new();
+            let url = format!(
+                "https://meta.quiltmc.org/v3/versions/loader/{}",
+                game_version
+            );
+            let response = client
+                .get(&url)
+                .header("User-Agent", format!("PaletheaLauncher/{}", minecraft::get_launcher_version()))
+                .send()
+                .await
+                .map_err(|e| format!("Network error connecting to Quilt meta: {}", e))?;
+            
+            if !response.status().is_success() {
+                let status = response.status();
+                if status == reqwest::StatusCode::NOT_FOUND {
+                    return Err(format!("Quilt does not yet support Minecraft version {}. It might be too new or invalid.", game_version));
+                }
+                return Err(format!("Quilt meta returned error status: {} ({})", status.as_u16(), status.canonical_reason().unwrap_or("Unknown")));
+            }
+            
+            #[derive(Deserialize)]
+            struct QuiltLoaderVersion {
+                loader: QuiltLoader,
+            }
+            
+            #[derive(Deserialize)]
+            struct QuiltLoader {
+                version: String,
+            }
+            
+            let versions: Vec<QuiltLoaderVersion> = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse Quilt meta response: {}. The version '{}' might not have loader support yet.", e, game_version))?;
+            
+            Ok(versions.into_iter().map(|v| {
+                let lower_v = v.loader.version.to_lowercase();
+                let v_type = if lower_v.contains("beta") || lower_v.contains("alpha") || lower_v.contains("rc") || lower_v.contains("pre") {
+                    "snapshot".to_string()
+                } else {
+                    "release".to_string()
+                };
+                
+                LoaderVersion {
+                    version: v.loader.version,
+                    release_time: None,
+                    version_type: v_type,
+                }
+            }).collect())
+        }
         "forge" => {
             // Fetch Forge versions
             let client = reqwest::Client::new();
@@ -2188,6 +2513,7 @@ async fn install_modrinth_file(
     author: Option<String>,
     icon_url: Option<String>,
     version_name: Option<String>,
+    allow_incompatible: Option<bool>,
 ) -> Result<(), String> {
     let instance = instances::get_instance(&instance_id)?;
     
@@ -2196,7 +2522,7 @@ async fn install_modrinth_file(
         "resourcepack" => files::get_resourcepacks_dir(&instance),
         "shader" => files::get_shaderpacks_dir(&instance),
         "datapack" => {
-            if let Some(wname) = world_name {
+            if let Some(wname) = &world_name {
                 files::get_saves_dir(&instance).join(wname).join("datapacks")
             } else {
                 return Err("World name required for datapack installation".to_string());
@@ -2206,6 +2532,8 @@ async fn install_modrinth_file(
     };
     
     let dest_path = dest_dir.join(&filename);
+    // Download next to the destination first so incompatible packs never land in the folder
+    let part_path = dest_dir.join(format!("{}.part", filename));
     
     // Download the file with progress
     let client = reqwest::Client::new();
@@ -2224,7 +2552,7 @@ async fn install_modrinth_file(
         std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
     }
     
-    let mut file = std::fs::File::create(&dest_path).map_err(|e| format!("Failed to create file: {}", e))?;
+    let mut file = std::fs::File::create(&part_path).map_err(|e| format!("Failed to create file: {}", e))?;
     let mut downloaded: u64 = 0;
     let mut last_emit = std::time::Instant::now();
     
@@ -2232,9 +2560,21 @@ async fn install_modrinth_file(
     use futures::StreamExt;
     
     while let Some(item) = stream.next().await {
-        let chunk = item.map_err(|e| format!("Download error: {}", e))?;
-        std::io::Write::write_all(&mut file, &chunk).map_err(|e| format!("Write error: {}", e))?;
-        downloaded += chunk.len() as u64;
+        let written = item
+            .map_err(|e| format!("Download error: {}", e))
+            .and_then(|chunk| {
+                std::io::Write::write_all(&mut file, &chunk).map_err(|e| format!("Write error: {}", e))?;
+                Ok(chunk.len() as u64)
+            });
+        let chunk_len = match written {
+            Ok(len) => len,
+            Err(e) => {
+                drop(file);
+                let _ = std::fs::remove_file(&part_path);
+                return Err(e);
+            }
+        };
+        downloaded += chunk_len;
         
         // Emit progress every 100ms or so
         if last_emit.elapsed().as_millis() > 100 || total_size.map_or(false, |ts| downloaded == ts) {
@@ -2250,6 +2590,37 @@ async fn install_modrinth_file(
             last_emit = std::time::Instant::now();
         }
     }
+    drop(file);
+
+    // Check pack.mcmeta against the instance version before placing the pack
+    let pack_kind = match file_type.as_str() {
+        "datapack" => Some(minecraft::pack_format::PackKind::Data),
+        "resourcepack" => Some(minecraft::pack_format::PackKind::Resource),
+        _ => None,
+    };
+    if let Some(kind) = pack_kind {
+        let data_version = world_name.as_ref()
+            .and_then(|w| files::read_world_data_version(&files::get_saves_dir(&instance).join(w)));
+        let format = minecraft::pack_format::check_pack(&part_path, kind, &instance.version_id, data_version);
+        if format.is_incompatible() && !allow_incompatible.unwrap_or(false) {
+            let _ = std::fs::remove_file(&part_path);
+            return Err(format!(
+                "Pack format {} is not supported by Minecraft {} (expects {})",
+                match (format.min_format, format.max_format) {
+                    (Some(min), Some(max)) if min != max => format!("{}-{}", min, max),
+                    (Some(v), _) | (_, Some(v)) => v.to_string(),
+                    _ => "unknown".to_string(),
+                },
+                instance.version_id,
+                format.expected_format.map(|f| f.to_string()).unwrap_or_else(|| "unknown".to_string()),
+            ));
+        }
+    }
+
+    if let Err(e) = std::fs::rename(&part_path, &dest_path) {
+        let _ = std::fs::remove_file(&part_path);
+        return Err(format!("Failed to move downloaded file: {}", e));
+    }
     
     // Save metadata with project_id if provided
     if let Some(pid) = project_id {
@@ -2360,6 +2731,83 @@ fn rename_instance_world(instance_id: String, folder_name: String, new_name: Str
     files::rename_world(&instance, &folder_name, &new_name)
 }
 
+#[tauri::command]
+fn get_world_details(instance_id: String, folder_name: String) -> Result<files::WorldDetails, String> {
+    let instance = instances::get_instance(&instance_id)?;
+    files::get_world_details(&instance, &folder_name)
+}
+
+#[tauri::command]
+fn update_world_settings(instance_id: String, folder_name: String, update: files::WorldSettingsUpdate) -> Result<String, String> {
+    let instance = instances::get_instance(&instance_id)?;
+    {
+        let processes = RUNNING_PROCESSES.lock().map_err(|_| "Process state corrupted")?;
+        if processes.contains_key(&instance_id) {
+            return Err("Close the game before editing world settings".to_string());
+        }
+    }
+    files::update_world_settings(&instance, &folder_name, &update)
+}
+
+#[tauri::command]
+async fn backup_world(instance_id: String, folder_name: String) -> Result<backups::WorldBackup, String> {
+    let instance = instances::get_instance(&instance_id)?;
+    tokio::task::spawn_blocking(move || {
+        backups::backup_world(&instance, &folder_name, "manual")
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn analyze_world_regions(instance_id: String, folder_name: String, app_handle: AppHandle) -> Result<regions::WorldRegionAnalysis, String> {
+    let instance = instances::get_instance(&instance_id)?;
+    tokio::task::spawn_blocking(move || {
+        regions::analyze_world(&app_handle, &instance, &folder_name)
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn prune_world_chunks(instance_id: String, folder_name: String, min_inhabited_ticks: i64, app_handle: AppHandle) -> Result<regions::PruneResult, String> {
+    let instance = instances::get_instance(&instance_id)?;
+    {
+        let processes = RUNNING_PROCESSES.lock().map_err(|_| "Process state corrupted")?;
+        if processes.contains_key(&instance_id) {
+            return Err("Close the game before pruning a world".to_string());
+        }
+    }
+    let handle = app_handle.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        regions::prune_world_chunks(&handle, &instance, &folder_name, min_inhabited_ticks)
+    }).await.map_err(|e| e.to_string())??;
+    log_info!(&app_handle, "Pruned {} chunks ({} bytes) from world, backup: {}", result.chunks_removed, result.bytes_freed, result.backup.filename);
+    Ok(result)
+}
+
+#[tauri::command]
+fn list_world_backups(instance_id: String, folder_name: Option<String>) -> Result<Vec<backups::WorldBackup>, String> {
+    let instance = instances::get_instance(&instance_id)?;
+    Ok(backups::list_world_backups(&instance, folder_name.as_deref()))
+}
+
+#[tauri::command]
+async fn restore_world_backup(instance_id: String, filename: String) -> Result<Option<backups::WorldBackup>, String> {
+    let instance = instances::get_instance(&instance_id)?;
+    {
+        let processes = RUNNING_PROCESSES.lock().map_err(|_| "Process state corrupted")?;
+        if processes.contains_key(&instance_id) {
+            return Err("Close the game before restoring a world backup".to_string());
+        }
+    }
+    tokio::task::spawn_blocking(move || {
+        backups::restore_world_backup(&instance, &filename)
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+fn delete_world_backup(instance_id: String, filename: String) -> Result<(), String> {
+    let instance = instances::get_instance(&instance_id)?;
+    backups::delete_world_backup(&instance, &filename)
+}
+
 #[tauri::command]
 fn open_instance_world_folder(_app: AppHandle, instance_id: String, folder_name: String) -> Result<(), String> {
     let instance = instances::get_instance(&instance_id)?;
@@ -2514,8 +2962,86 @@ fn set_server_resource_packs(instance_id: String, index: usize, mode: i8) -> Res
 }
 
 #[tauri::command]
-async fn ping_server(address: String) -> Result<minecraft::ping::PingResponse, String> {
-    minecraft::ping::ping_server(&address).await
+async fn ping_server(address: String, instance_id: Option<String>) -> Result<minecraft::ping::PingResponse, String> {
+    // Ping with the instance's protocol so the server reports compatibility for it
+    let protocol = match instance_id {
+        Some(id) => minecraft::ping::protocol_for_version(&instances::get_instance(&id)?.version_id),
+        None => None,
+    };
+    minecraft::ping::ping_server(&address, protocol).await
+}
+
+#[tauri::command]
+async fn query_server(address: String, query_port: Option<u16>) -> Result<minecraft::query::QueryResponse, String> {
+    minecraft::query::query_server(&address, query_port).await
+}
+
+#[tauri::command]
+async fn start_lan_discovery(app_handle: AppHandle) -> Result<(), String> {
+    minecraft::lan::start_discovery(app_handle)
+}
+
+#[tauri::command]
+fn stop_lan_discovery() {
+    minecraft::lan::stop_discovery()
+}
+
+#[tauri::command]
+fn get_lan_games() -> Vec<minecraft::lan::LanGame> {
+    minecraft::lan::get_lan_games()
+}
+
+#[tauri::command]
+fn join_lan_game(instance_id: String, address: String) -> Result<files::Server, String> {
+    let instance = instances::get_instance(&instance_id)?;
+    let game = minecraft::lan::find_lan_game(&address).ok_or("LAN game is no longer available")?;
+
+    let mut servers = files::list_servers(&instance);
+    if let Some(existing) = servers.iter().find(|s| s.ip == game.address) {
+        return Ok(existing.clone());
+    }
+
+    let server = files::Server {
+        name: game.motd,
+        ip: game.address,
+        icon: None,
+        accept_textures: 0,
+    };
+    servers.push(server.clone());
+    files::save_servers(&instance, servers)?;
+    Ok(server)
+}
+
+/// Launch the instance straight into a LAN game with Quick Play instead of adding it to the server list
+#[tauri::command]
+async fn quick_play_lan_game(
+    instance_id: String,
+    address: String,
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<String, String> {
+    let game = minecraft::lan::find_lan_game(&address).ok_or("LAN game is no longer available")?;
+    let target = launcher::QuickPlayTarget::Server { address: game.address };
+    launch_instance(instance_id, Some(target), state, app_handle).await
+}
+
+#[tauri::command]
+async fn ping_servers(instance_id: Option<String>, app_handle: AppHandle) -> Result<Vec<server_status::ServerListEntry>, String> {
+    let targets = match instance_id {
+        Some(id) => vec![instances::get_instance(&id)?],
+        None => instances::load_instances()?,
+    };
+    let servers = server_status::collect_servers(&targets);
+
+    // Results stream in through events, cached status is returned right away
+    tokio::spawn(server_status::ping_servers(app_handle, servers.clone()));
+
+    Ok(servers)
+}
+
+#[tauri::command]
+fn get_server_latency_history(address: String) -> Vec<server_status::LatencySample> {
+    server_status::get_latency_history(&address)
 }
 
 #[tauri::command]
@@ -2551,6 +3077,22 @@ fn import_instance_file(instance_id: String, source_path: String, folder_type: S
     Ok(())
 }
 
+#[tauri::command]
+async fn import_world_zip(instance_id: String, source_path: String, app_handle: AppHandle) -> Result<files::ImportedWorld, String> {
+    let instance = instances::get_instance(&instance_id)?;
+    let source = std::path::PathBuf::from(&source_path);
+    if !source.exists() {
+        return Err("Source file not found".to_string());
+    }
+
+    let imported = tokio::task::spawn_blocking(move || {
+        files::import_world_zip(&instance, &source)
+    }).await.map_err(|e| e.to_string())??;
+
+    log_info!(&app_handle, "Imported world '{}' as {}", imported.name, imported.folder_name);
+    Ok(imported)
+}
+
 #[tauri::command]
 async fn open_instance_folder(_app: AppHandle, instance_id: String, folder_type: String) -> Result<(), String> {
     let instance = instances::get_instance(&instance_id)?;
@@ -2657,7 +3199,11 @@ fn get_instance_details(instance_id: String) -> Result<instances::Instance, Stri
 #[tauri::command]
 fn exit_app_fully(app: AppHandle) {
     let mut processes = RUNNING_PROCESSES.lock().expect("Process state corrupted");
-    for (_instance_id, info) in processes.drain() {
+    // The user confirmed stopping the running games along with the launcher, so those sessions
+    // count as killed if their exit threads get to record them before the app goes down
+    let mut killed = USER_KILLED.lock().expect("Process state corrupted");
+    for (instance_id, info) in processes.drain() {
+        killed.insert(instance_id);
         #[cfg(target_os = "windows")]
         {
             let _ = std::process::Command::new("taskkill")
@@ -2671,8 +3217,8 @@ fn exit_app_fully(app: AppHandle) {
                 .spawn();
         }
     }
-    // Clear session file
-    instances::clear_active_session();
+    // Clear session files
+    instances::clear_all_sessions();
     app.exit(0);
 }
 
@@ -2740,55 +3286,63 @@ pub fn run() {
             }
             let _ = fs::create_dir_all(downloader::get_skins_dir());
 
-            // Recover any orphaned playtime sessions from crashes or re-register running processes
-            if let Some((instance_id, start_time, pid, current_playtime)) = instances::recover_orphaned_session() {
-                let handle = app.handle().clone();
-                let _ = handle.emit("refresh-instances", ());
-
-                if let Some(pid_val) = pid {
-                    // Re-register in the global map
-                    {
-                        let mut processes = RUNNING_PROCESSES.lock().expect("Process state corrupted");
-                        processes.insert(instance_id.clone(), RunningProcessInfo {
-                            pid: pid_val,
-                            start_time,
-                        });
+            // Recover orphaned playtime sessions from crashes and re-register running processes
+            let recovered = instances::recover_orphaned_sessions();
+            if !recovered.is_empty() {
+                let _ = app.handle().emit("refresh-instances", ());
+            }
+            for session in recovered {
+                let Some(pid_val) = session.pid else {
+                    if let Ok(inst) = instances::get_instance(&session.instance_id) {
+                        let end_time = session.start_time + session.credited_seconds;
+                        let _ = minecraft::history::record_session(&minecraft::history::PlaySession::recovered(&inst, session.start_time, end_time));
+                    }
+                    log::info!("Recovered orphaned session for instance {}: {}s credited", session.instance_id, session.credited_seconds);
+                    continue;
+                };
+                let start_time = session.start_time;
+
+                // Re-register in the global map
+                {
+                    let mut processes = RUNNING_PROCESSES.lock().expect("Process state corrupted");
+                    processes.insert(session.instance_id.clone(), RunningProcessInfo {
+                        pid: pid_val,
+                        start_time,
+                    });
+                }
+                
+                let handle_clone = app.handle().clone();
+                let instance_id_clone = session.instance_id.clone();
+                
+                std::thread::spawn(move || {
+                    // Wait for process to stop
+                    while instances::is_process_running(pid_val) {
+                        std::thread::sleep(std::time::Duration::from_secs(5));
                     }
                     
-                    let handle_clone = handle.clone();
-                    let instance_id_clone = instance_id.clone();
+                    // Finalize playtime
+                    let end_time = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_secs();
+                    let session_duration = end_time.saturating_sub(start_time);
                     
-                    std::thread::spawn(move || {
-                        // Wait for process to stop
-                        while instances::is_process_running(pid_val) {
-                            std::thread::sleep(std::time::Duration::from_secs(5));
-                        }
-                        
-                        // Finalize playtime
-                        let end_time = std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_secs();
-                        let session_duration = end_time.saturating_sub(start_time);
-                        
-                        if let Ok(mut inst) = instances::get_instance(&instance_id_clone) {
-                            inst.playtime_seconds = current_playtime + session_duration;
-                            let _ = instances::update_instance(inst);
-                        }
-                        
-                        instances::clear_active_session();
-                        
-                        if let Ok(mut processes) = RUNNING_PROCESSES.lock() {
-                            processes.remove(&instance_id_clone);
-                        }
-                        
-                        let _ = handle_clone.emit("refresh-instances", ());
-                    });
+                    if let Ok(mut inst) = instances::get_instance(&instance_id_clone) {
+                        let _ = minecraft::history::record_session(&minecraft::history::PlaySession::recovered(&inst, start_time, end_time));
+                        inst.playtime_seconds += session_duration;
+                        let _ = instances::update_instance(inst);
+                    }
                     
-                    log::info!("Re-registered running instance {} with PID {}", instance_id, pid_val);
-                } else {
-                    log::info!("Recovered orphaned session for instance {}: {}s credited", instance_id, current_playtime);
-                }
+                    instances::clear_active_session(&instance_id_clone);
+                    
+                    if let Ok(mut processes) = RUNNING_PROCESSES.lock() {
+                        processes.remove(&instance_id_clone);
+                    }
+                    
+                    let _ = handle_clone.emit("refresh-instances", ());
+                });
+                
+                log::info!("Re-registered running instance {} with PID {}", session.instance_id, pid_val);
             }
             Ok(())
         })
@@ -2818,6 +3372,8 @@ pub fn run() {
             is_version_downloaded,
             // Launch commands
             launch_instance,
+            preview_launch,
+            export_launch_script,
             kill_game,
             get_running_instances,
             check_java,
@@ -2829,6 +3385,11 @@ pub fn run() {
             download_java_for_instance,
             download_java_global,
             is_java_version_installed,
+            list_java_runtimes,
+            install_java_runtime,
+            remove_java_runtime,
+            validate_java_runtime,
+            get_memory_recommendation,
             // Update/version comparison commands
             get_github_releases,
             compare_versions,
@@ -2857,6 +3418,7 @@ pub fn run() {
             install_fabric,
             install_forge,
             install_neoforge,
+            install_quilt,
             // Disk cleanup commands
             get_disk_usage,
             get_downloaded_versions,
@@ -2883,8 +3445,17 @@ pub fn run() {
             get_instance_worlds,
             delete_instance_world,
             rename_instance_world,
+            get_world_details,
+            update_world_settings,
+            backup_world,
+            list_world_backups,
+            restore_world_backup,
+            delete_world_backup,
+            analyze_world_regions,
+            prune_world_chunks,
             open_instance_world_folder,
             import_instance_file,
+            import_world_zip,
             open_instance_datapacks_folder,
             get_world_datapacks,
             delete_instance_datapack,
@@ -2900,6 +3471,14 @@ pub fn run() {
             update_instance_server,
             set_server_resource_packs,
             ping_server,
+            query_server,
+            ping_servers,
+            start_lan_discovery,
+            stop_lan_discovery,
+            get_lan_games,
+            join_lan_game,
+            quick_play_lan_game,
+            get_server_latency_history,
             open_instance_folder,
             get_instance_share_code,
             get_instance_mods_share_code,
@@ -2910,6 +3489,13 @@ pub fn run() {
             delete_skin_from_collection,
             get_skin_file_path,
             get_global_stats,
+            get_session_history,
+            get_playtime_by_day,
+            get_playtime_by_week,
+            get_instance_session_stats,
+            get_crash_stats,
+            get_play_streaks,
+            export_session_history,
             log_event,
             get_bootstrap_time,
             exit_app_fully,
@@ -0,0 +1,339 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{Read, Seek, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zip::write::SimpleFileOptions;
+use zip::ZipArchive;
+
+use crate::minecraft::files::{extract_world_entries, get_saves_dir, read_world_level_name};
+use crate::minecraft::instances::Instance;
+
+const BACKUP_METADATA_FILE: &str = "palethea_backup.json";
+const DEFAULT_KEEP_LAST: u32 = 5;
+const DEFAULT_KEEP_DAYS: u32 = 7;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorldBackup {
+    pub filename: String,
+    pub folder_name: String,
+    pub level_name: String,
+    pub created_at: u64,
+    pub reason: String, // "manual", "auto", "pre-restore" or "pre-prune"
+    #[serde(default)]
+    pub size: u64,
+}
+
+/// Get the world backups directory for an instance
+pub fn get_world_backups_dir(instance: &Instance) -> PathBuf {
+    instance.get_directory().join("world_backups")
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Recursively add a world folder to the zip under the given prefix
+fn add_dir_to_zip<W: Write + Seek>(
+    zip: &mut zip::ZipWriter<W>,
+    base_path: &Path,
+    current_path: &Path,
+    prefix: &str,
+    options: SimpleFileOptions,
+) -> Result<(), String> {
+    for entry in fs::read_dir(current_path).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+        let relative = path.strip_prefix(base_path).map_err(|e| e.to_string())?;
+        let name = format!("{}/{}", prefix, relative.to_string_lossy().replace("\\", "/"));
+
+        if path.is_dir() {
+            zip.add_directory(&name, options)
+                .map_err(|e| format!("Failed to add directory {}: {}", name, e))?;
+            add_dir_to_zip(zip, base_path, &path, prefix, options)?;
+        } else {
+            // session.lock is held open by a running game and is recreated on load anyway
+            if entry.file_name() == "session.lock" {
+                continue;
+            }
+            zip.start_file(&name, options)
+                .map_err(|e| format!("Failed to start file {}: {}", name, e))?;
+            let mut file = File::open(&path).map_err(|e| e.to_string())?;
+            std::io::copy(&mut file, zip).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+fn write_backup_zip(zip_path: &Path, world_path: &Path, folder_name: &str, backup: &WorldBackup) -> Result<(), String> {
+    let file = File::create(zip_path)
+        .map_err(|e| format!("Failed to create backup file: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .compression_level(Some(6));
+
+    zip.start_file(BACKUP_METADATA_FILE, options)
+        .map_err(|e| format!("Failed to add backup metadata: {}", e))?;
+    let metadata = serde_json::to_string_pretty(backup).map_err(|e| e.to_string())?;
+    zip.write_all(metadata.as_bytes())
+        .map_err(|e| format!("Failed to write backup metadata: {}", e))?;
+
+    add_dir_to_zip(&mut zip, world_path, world_path, folder_name, options)?;
+    zip.finish().map_err(|e| format!("Failed to finalize backup: {}", e))?;
+    Ok(())
+}
+
+/// Zip a world folder into the instance's backup directory
+pub fn backup_world(instance: &Instance, folder_name: &str, reason: &str) -> Result<WorldBackup, String> {
+    let world_path = get_saves_dir(instance).join(folder_name);
+    if !world_path.join("level.dat").exists() {
+        return Err("World folder not found".to_string());
+    }
+
+    let backups_dir = get_world_backups_dir(instance);
+    fs::create_dir_all(&backups_dir)
+        .map_err(|e| format!("Failed to create backups directory: {}", e))?;
+
+    let level_name = read_world_level_name(&world_path).unwrap_or_else(|| folder_name.to_string());
+    let created_at = now_secs();
+    let stamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
+
+    // Two backups within the same second get a numeric suffix
+    let mut filename = format!("{}_{}.zip", folder_name, stamp);
+    let mut suffix = 1;
+    while backups_dir.join(&filename).exists() {
+        filename = format!("{}_{}-{}.zip", folder_name, stamp, suffix);
+        suffix += 1;
+    }
+
+    let mut backup = WorldBackup {
+        filename: filename.clone(),
+        folder_name: folder_name.to_string(),
+        level_name,
+        created_at,
+        reason: reason.to_string(),
+        size: 0,
+    };
+
+    // Write to a partial file first so an interrupted backup never looks valid
+    let partial_path = backups_dir.join(format!("{}.partial", filename));
+    let result = write_backup_zip(&partial_path, &world_path, folder_name, &backup);
+    if let Err(e) = result {
+        let _ = fs::remove_file(&partial_path);
+        return Err(e);
+    }
+
+    let final_path = backups_dir.join(&filename);
+    fs::rename(&partial_path, &final_path).map_err(|e| format!("Failed to save backup: {}", e))?;
+    backup.size = fs::metadata(&final_path).map(|m| m.len()).unwrap_or(0);
+
+    log::info!("Backed up world {} to {}", folder_name, final_path.display());
+    Ok(backup)
+}
+
+/// Read the metadata stored inside a backup zip
+fn read_backup_metadata(path: &Path) -> Option<WorldBackup> {
+    let file = File::open(path).ok()?;
+    let mut archive = ZipArchive::new(file).ok()?;
+    let mut contents = String::new();
+    archive.by_name(BACKUP_METADATA_FILE).ok()?.read_to_string(&mut contents).ok()?;
+
+    let mut backup: WorldBackup = serde_json::from_str(&contents).ok()?;
+    backup.filename = path.file_name()?.to_string_lossy().to_string();
+    backup.size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    Some(backup)
+}
+
+/// List backups, optionally only those of a single world (newest first)
+pub fn list_world_backups(instance: &Instance, folder_name: Option<&str>) -> Vec<WorldBackup> {
+    let dir = get_world_backups_dir(instance);
+    let mut backups = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(&dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().map_or(true, |ext| ext != "zip") {
+                continue;
+            }
+            if let Some(backup) = read_backup_metadata(&path) {
+                if folder_name.map_or(true, |f| f == backup.folder_name) {
+                    backups.push(backup);
+                }
+            }
+        }
+    }
+
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.filename.cmp(&a.filename)));
+    backups
+}
+
+/// Delete a world backup
+pub fn delete_world_backup(instance: &Instance, filename: &str) -> Result<(), String> {
+    let path = resolve_backup_path(instance, filename)?;
+    fs::remove_file(&path).map_err(|e| e.to_string())
+}
+
+fn resolve_backup_path(instance: &Instance, filename: &str) -> Result<PathBuf, String> {
+    // Only plain file names are accepted, never paths
+    if Path::new(filename).file_name().map_or(true, |n| n != filename) {
+        return Err("Invalid backup filename".to_string());
+    }
+    let path = get_world_backups_dir(instance).join(filename);
+    if !path.exists() {
+        return Err("Backup not found".to_string());
+    }
+    Ok(path)
+}
+
+/// Restore a backup into the saves folder.
+/// If the world currently exists it is backed up first, so the live world is never lost.
+/// Returns the safety backup that was taken, if any.
+pub fn restore_world_backup(instance: &Instance, filename: &str) -> Result<Option<WorldBackup>, String> {
+    let backup_path = resolve_backup_path(instance, filename)?;
+    let metadata = read_backup_metadata(&backup_path)
+        .ok_or("This doesn't appear to be a valid world backup")?;
+
+    // The folder name comes from inside the zip, it must stay a single folder under saves
+    let mut components = Path::new(&metadata.folder_name).components();
+    if !matches!((components.next(), components.next()), (Some(Component::Normal(_)), None)) {
+        return Err("This backup has an invalid world folder name".to_string());
+    }
+
+    let saves_dir = get_saves_dir(instance);
+    fs::create_dir_all(&saves_dir).map_err(|e| e.to_string())?;
+    let live_path = saves_dir.join(&metadata.folder_name);
+
+    let safety_backup = if live_path.join("level.dat").exists() {
+        Some(backup_world(instance, &metadata.folder_name, "pre-restore")
+            .map_err(|e| format!("Refusing to restore, could not back up the current world: {}", e))?)
+    } else {
+        None
+    };
+
+    // Extract next to the live world, then swap, so a failed extraction leaves it untouched
+    let staging_path = saves_dir.join(format!(".{}.restoring", metadata.folder_name));
+    if staging_path.exists() {
+        fs::remove_dir_all(&staging_path).map_err(|e| e.to_string())?;
+    }
+
+    if let Err(e) = extract_backup(&backup_path, &metadata.folder_name, &staging_path) {
+        let _ = fs::remove_dir_all(&staging_path);
+        return Err(e);
+    }
+
+    if live_path.exists() {
+        let old_path = saves_dir.join(format!(".{}.old", metadata.folder_name));
+        if old_path.exists() {
+            fs::remove_dir_all(&old_path).map_err(|e| e.to_string())?;
+        }
+        fs::rename(&live_path, &old_path)
+            .map_err(|e| format!("Failed to move current world aside: {}", e))?;
+        if let Err(e) = fs::rename(&staging_path, &live_path) {
+            let _ = fs::rename(&old_path, &live_path);
+            return Err(format!("Failed to move restored world into place: {}", e));
+        }
+        let _ = fs::remove_dir_all(&old_path);
+    } else {
+        fs::rename(&staging_path, &live_path)
+            .map_err(|e| format!("Failed to move restored world into place: {}", e))?;
+    }
+
+    log::info!("Restored world {} from {}", metadata.folder_name, filename);
+    Ok(safety_backup)
+}
+
+fn extract_backup(backup_path: &Path, folder_name: &str, dest: &Path) -> Result<(), String> {
+    let file = File::open(backup_path).map_err(|e| format!("Failed to open backup: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Failed to read backup: {}", e))?;
+    extract_world_entries(&mut archive, Path::new(folder_name), dest)
+}
+
+/// Apply the instance's retention policy to automatic backups of one world.
+/// Manual and pre-restore backups are never pruned. Returns the number of backups removed.
+pub fn apply_retention(instance: &Instance, folder_name: &str) -> Result<u32, String> {
+    let auto_backups: Vec<WorldBackup> = list_world_backups(instance, Some(folder_name))
+        .into_iter()
+        .filter(|b| b.reason == "auto")
+        .collect();
+
+    let mode = instance.backup_retention.as_deref().unwrap_or("keep_last");
+    let to_remove: Vec<&WorldBackup> = match mode {
+        // Keep the newest backup of each of the last N days that have backups
+        "keep_daily" => {
+            let days = instance.backup_retention_count.unwrap_or(DEFAULT_KEEP_DAYS).max(1) as usize;
+            let mut seen_days = HashSet::new();
+            auto_backups
+                .iter()
+                .filter(|b| {
+                    let day = chrono::DateTime::from_timestamp(b.created_at as i64, 0)
+                        .map(|d| d.with_timezone(&chrono::Local).date_naive());
+                    if seen_days.contains(&day) {
+                        return true;
+                    }
+                    seen_days.insert(day);
+                    seen_days.len() > days
+                })
+                .collect()
+        }
+        _ => {
+            let keep = instance.backup_retention_count.unwrap_or(DEFAULT_KEEP_LAST).max(1) as usize;
+            auto_backups.iter().skip(keep).collect()
+        }
+    };
+
+    let dir = get_world_backups_dir(instance);
+    let mut removed = 0;
+    for backup in to_remove {
+        if fs::remove_file(dir.join(&backup.filename)).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Back up every world that was played during a session, then apply retention.
+/// Called from the exit-watching thread once the game has closed.
+pub fn backup_worlds_after_session(instance: &Instance, session_start: u64) -> Vec<Result<WorldBackup, String>> {
+    let saves_dir = get_saves_dir(instance);
+    let mut results = Vec::new();
+
+    let entries = match fs::read_dir(&saves_dir) {
+        Ok(entries) => entries,
+        Err(_) => return results,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let level_dat = path.join("level.dat");
+        if !level_dat.exists() {
+            continue;
+        }
+
+        // The game rewrites level.dat on every save, so an older one means the world wasn't opened
+        let modified = fs::metadata(&level_dat)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        if modified < session_start {
+            continue;
+        }
+
+        let folder_name = entry.file_name().to_string_lossy().to_string();
+        let result = backup_world(instance, &folder_name, "auto");
+        if result.is_ok() {
+            if let Err(e) = apply_retention(instance, &folder_name) {
+                log::warn!("Failed to apply backup retention for {}: {}", folder_name, e);
+            }
+        }
+        results.push(result);
+    }
+
+    results
+}
@@ -1,12 +1,14 @@
+use crate::minecraft::natives;
 use crate::minecraft::versions::{self, VersionDetails, should_use_library};
 use serde::{Deserialize, Serialize};
 use sha1::{Sha1, Digest};
 use std::fs::{self, File};
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::error::Error;
 use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
 use tauri::{AppHandle, Emitter, Manager};
 use futures::stream::{self, StreamExt};
 use crate::log_info;
@@ -122,11 +124,88 @@ pub fn sync_logos(app_handle: &AppHandle) -> Result<(), String> {
 }
 
 
-/// Verify a file's SHA1 hash
+/// A file whose SHA1 was checked, valid while its size and mtime are unchanged
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct VerifiedFile {
+    size: u64,
+    modified_ms: u64,
+    sha1: String,
+}
+
+/// Verified files keyed by path, loaded from verified_files.json on first use
+static VERIFIED_FILES: LazyLock<Mutex<VerificationCache>> = LazyLock::new(|| {
+    let files = fs::read_to_string(get_verification_cache_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    Mutex::new(VerificationCache { files, dirty: false })
+});
+
+struct VerificationCache {
+    files: HashMap<String, VerifiedFile>,
+    dirty: bool,
+}
+
+fn get_verification_cache_path() -> PathBuf {
+    get_minecraft_dir().join("verified_files.json")
+}
+
+/// Size and modification time (ms) of a file
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?
+        .duration_since(std::time::UNIX_EPOCH).ok()?
+        .as_millis() as u64;
+    Some((metadata.len(), modified))
+}
+
+fn is_known_verified(path: &Path, expected: &str) -> bool {
+    let Some((size, modified_ms)) = file_stamp(path) else { return false };
+    let Ok(cache) = VERIFIED_FILES.lock() else { return false };
+    cache.files.get(&path.to_string_lossy().to_string())
+        .map_or(false, |f| f.size == size && f.modified_ms == modified_ms && f.sha1.eq_ignore_ascii_case(expected))
+}
+
+fn record_verified(path: &Path, sha1: &str) {
+    let Some((size, modified_ms)) = file_stamp(path) else { return };
+    if let Ok(mut cache) = VERIFIED_FILES.lock() {
+        cache.files.insert(path.to_string_lossy().to_string(), VerifiedFile {
+            size,
+            modified_ms,
+            sha1: sha1.to_string(),
+        });
+        cache.dirty = true;
+    }
+}
+
+/// Persist newly verified files so the next launch can skip hashing them
+pub fn save_verification_cache() {
+    let Ok(mut cache) = VERIFIED_FILES.lock() else { return };
+    if !cache.dirty {
+        return;
+    }
+    // Forget files that have since been deleted
+    cache.files.retain(|path, _| Path::new(path).exists());
+    match serde_json::to_string(&cache.files) {
+        Ok(json) => {
+            if let Err(e) = fs::write(get_verification_cache_path(), json) {
+                log::warn!("Failed to save verification cache: {}", e);
+            } else {
+                cache.dirty = false;
+            }
+        }
+        Err(e) => log::warn!("Failed to serialize verification cache: {}", e),
+    }
+}
+
+/// Check a file's SHA1, skipping the hash when the file is unchanged since it was last verified
 pub fn verify_sha1(path: &PathBuf, expected: &str) -> bool {
     if !path.exists() {
         return false;
     }
+    if is_known_verified(path, expected) {
+        return true;
+    }
     
     let mut file = match File::open(path) {
         Ok(f) => f,
@@ -146,7 +225,11 @@ pub fn verify_sha1(path: &PathBuf, expected: &str) -> bool {
     
     let result = hasher.finalize();
     let hash = format!("{:x}", result);
-    hash == expected
+    if !hash.eq_ignore_ascii_case(expected) {
+        return false;
+    }
+    record_verified(path, &hash);
+    true
 }
 
 /// Download a file with progress tracking
@@ -197,36 +280,131 @@ pub async fn download_file(
     Ok(())
 }
 
+/// Client jar a version launches with, inherited versions share their parent's
+pub fn get_client_jar_path(version_details: &VersionDetails) -> PathBuf {
+    let jar_id = version_details.client_jar_id();
+    get_versions_dir().join(jar_id).join(format!("{}.jar", jar_id))
+}
+
 /// Download the client JAR for a version
 pub async fn download_client(version_details: &VersionDetails) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
     let versions_dir = get_versions_dir();
-    let version_dir = versions_dir.join(&version_details.id);
-    let client_path = version_dir.join(format!("{}.jar", &version_details.id));
+    let client_path = get_client_jar_path(version_details);
     
     if let Some(downloads) = &version_details.downloads {
         let download_info = &downloads.client;
         download_file(&download_info.url, &client_path, Some(&download_info.sha1)).await?;
     }
     
-    // Also save the version JSON
-    let json_path = version_dir.join(format!("{}.json", &version_details.id));
-    let json_content = serde_json::to_string_pretty(version_details)?;
-    fs::write(&json_path, json_content)?;
+    // Also save the version JSON, resolved versions keep their original inheritsFrom JSON
+    if version_details.jar.is_none() {
+        let version_dir = versions_dir.join(&version_details.id);
+        fs::create_dir_all(&version_dir)?;
+        let json_path = version_dir.join(format!("{}.json", &version_details.id));
+        let json_content = serde_json::to_string_pretty(version_details)?;
+        fs::write(&json_path, json_content)?;
+    }
+    save_verification_cache();
     
     Ok(client_path)
 }
 
+/// Read the flags of a version's asset index from disk as (virtual, map_to_resources)
+fn read_asset_index_layout(version_details: &VersionDetails) -> Option<(serde_json::Value, bool, bool)> {
+    let asset_index = version_details.asset_index.as_ref()?;
+    let index_path = get_assets_dir().join("indexes").join(format!("{}.json", &asset_index.id));
+    let index_json: serde_json::Value = serde_json::from_str(&fs::read_to_string(index_path).ok()?).ok()?;
+
+    let is_virtual = index_json.get("virtual").and_then(|v| v.as_bool()).unwrap_or(false);
+    let map_to_resources = index_json.get("map_to_resources").and_then(|v| v.as_bool()).unwrap_or(false);
+    Some((index_json, is_virtual, map_to_resources))
+}
+
+/// Directory the game expects its assets in (${game_assets}). Modern versions use the shared
+/// object store, "virtual" indexes a named tree under assets/virtual and
+/// "map_to_resources" indexes (pre-1.6) the instance's resources folder.
+pub fn get_game_assets_dir(version_details: &VersionDetails, game_dir: &Path) -> PathBuf {
+    let assets_dir = get_assets_dir();
+    match (read_asset_index_layout(version_details), &version_details.asset_index) {
+        (Some((_, _, true)), _) => game_dir.join("resources"),
+        (Some((_, true, _)), Some(index)) => assets_dir.join("virtual").join(&index.id),
+        _ => assets_dir,
+    }
+}
+
+/// Copy assets out of the object store into the layout a legacy index asks for.
+/// Files already in place with the right size are left alone.
+pub fn prepare_legacy_assets(version_details: &VersionDetails, game_dir: &Path) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some((index_json, is_virtual, map_to_resources)) = read_asset_index_layout(version_details) else {
+        return Ok(());
+    };
+    if !is_virtual && !map_to_resources {
+        return Ok(());
+    }
+
+    let target_dir = get_game_assets_dir(version_details, game_dir);
+    let objects_dir = get_assets_dir().join("objects");
+    let mut copied = 0;
+
+    if let Some(objects) = index_json.get("objects").and_then(|o| o.as_object()) {
+        for (name, info) in objects {
+            let Some(hash) = info.get("hash").and_then(|h| h.as_str()) else { continue };
+            if hash.len() < 2 {
+                continue;
+            }
+            let size = info.get("size").and_then(|s| s.as_u64()).unwrap_or(0);
+            let source = objects_dir.join(&hash[..2]).join(hash);
+            let target = target_dir.join(name);
+
+            if fs::metadata(&target).map(|m| m.len() == size).unwrap_or(false) || !source.exists() {
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&source, &target)?;
+            copied += 1;
+        }
+    }
+
+    if copied > 0 {
+        log::info!("Copied {} legacy assets to {}", copied, target_dir.display());
+    }
+    Ok(())
+}
+
+/// Download the log4j config a version ships (logging.client) into assets/log_configs
+pub async fn download_log_config(version_details: &VersionDetails) -> Result<Option<PathBuf>, Box<dyn Error + Send + Sync>> {
+    let Some(config) = version_details.logging.as_ref().and_then(|l| l.client.as_ref()) else {
+        return Ok(None);
+    };
+
+    let path = get_log_config_path(config);
+    download_file(&config.file.url, &path, Some(&config.file.sha1)).await?;
+    Ok(Some(path))
+}
+
+/// Where a version's log4j config is stored
+pub fn get_log_config_path(config: &versions::LoggingConfig) -> PathBuf {
+    get_assets_dir().join("log_configs").join(&config.file.id)
+}
+
 /// Download all libraries for a version
 pub async fn download_libraries(version_details: &VersionDetails, app_handle: Option<&AppHandle>) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    download_libraries_in_range(version_details, app_handle, 10.0, 25.0).await
+}
+
+struct LibDownload {
+    url: String,
+    path: PathBuf,
+    sha1: String,
+    size: u64,
+}
+
+/// Every library file a version uses on this OS, with the total size of the known ones
+fn library_downloads(version_details: &VersionDetails) -> (Vec<LibDownload>, u64) {
     let libraries_dir = get_libraries_dir();
-    
-    // Collect all download tasks
-    struct LibDownload {
-        url: String,
-        path: PathBuf,
-        sha1: String,
-        size: u64,
-    }
+    let version_details = &natives::apply_overrides(version_details);
     
     let mut downloads: Vec<LibDownload> = Vec::new();
     let mut total_bytes = 0u64;
@@ -249,13 +427,9 @@ pub async fn download_libraries(version_details: &VersionDetails, app_handle: Op
             }
             
             // Handle natives
-            if let Some(natives) = &library.natives {
-                let os_name = versions::get_os_name();
-                if let Some(classifier_key) = natives.get(os_name) {
+            if library.natives.is_some() {
+                if let Some(classifier_key) = versions::native_classifier(library) {
                     if let Some(classifiers) = &lib_downloads.classifiers {
-                        let arch = if cfg!(target_arch = "x86_64") { "64" } else { "32" };
-                        let classifier_key = classifier_key.replace("${arch}", arch);
-                        
                         if let Some(native_artifact) = classifiers.get(&classifier_key) {
                             let size = native_artifact.size as u64;
                             total_bytes += size;
@@ -297,6 +471,27 @@ pub async fn download_libraries(version_details: &VersionDetails, app_handle: Op
             });
         }
     }
+
+    (downloads, total_bytes)
+}
+
+/// Library files of a version that aren't on disk yet
+pub fn missing_libraries(version_details: &VersionDetails) -> Vec<PathBuf> {
+    library_downloads(version_details).0
+        .into_iter()
+        .map(|dl| dl.path)
+        .filter(|path| !path.exists())
+        .collect()
+}
+
+/// Download all libraries, reporting progress from `start` to `start + span` percent
+pub async fn download_libraries_in_range(
+    version_details: &VersionDetails,
+    app_handle: Option<&AppHandle>,
+    start: f32,
+    span: f32,
+) -> Result<Vec<PathBuf>, Box<dyn Error + Send + Sync>> {
+    let (downloads, total_bytes) = library_downloads(version_details);
     
     let total = downloads.len() as u32;
     let completed = Arc::new(AtomicU32::new(0));
@@ -308,7 +503,7 @@ pub async fn download_libraries(version_details: &VersionDetails, app_handle: Op
             stage: format!("Downloading libraries (0/{})", total),
             current: 0,
             total,
-            percentage: 10.0,
+            percentage: start,
             total_bytes: Some(total_bytes),
             downloaded_bytes: Some(0),
         });
@@ -331,7 +526,7 @@ pub async fn download_libraries(version_details: &VersionDetails, app_handle: Op
                 // Emit progress every 5 downloads
                 if done % 5 == 0 || done == total {
                     if let Some(handle) = &app_handle {
-                        let percentage = 10.0 + ((done as f32 * 25.0) / total as f32);
+                        let percentage = start + ((done as f32 * span) / total as f32);
                         let _ = handle.emit("download-progress", DownloadProgress {
                             stage: format!("Downloading libraries ({}/{})", done, total),
                             current: done,
@@ -350,6 +545,8 @@ pub async fn download_libraries(version_details: &VersionDetails, app_handle: Op
         .collect()
         .await;
     
+    save_verification_cache();
+    
     // Collect successful paths
     let mut library_paths = Vec::new();
     for result in results {
@@ -462,6 +659,8 @@ pub async fn download_assets(version_details: &VersionDetails, app_handle: Optio
         .collect()
         .await;
     
+    save_verification_cache();
+    
     // Check for errors
     for result in results {
         result?;
@@ -525,6 +724,7 @@ pub async fn download_version(version_id: &str, app_handle: Option<&AppHandle>)
         });
     }
     download_libraries(&version_details, app_handle).await?;
+    download_log_config(&version_details).await?;
     
     // Download assets (35-100%)
     if let Some(handle) = app_handle {
@@ -132,7 +132,7 @@ async fn download_library(
 }
 
 /// Download a library from a maven repository with SHA1 verification
-async fn download_library_with_sha1(
+pub async fn download_library_with_sha1(
     url_base: &str,
     maven: &str,
     libraries_dir: &PathBuf,
@@ -246,38 +246,21 @@ pub fn load_fabric_info(instance: &Instance) -> Option<FabricLoaderVersion> {
     serde_json::from_str(&content).ok()
 }
 
-/// Get Fabric classpath additions as (maven_name, absolute_path) pairs
-pub fn get_fabric_classpath(fabric_info: &FabricLoaderVersion) -> Vec<(String, String)> {
-    let libraries_dir = get_libraries_dir();
-    let mut classpath = Vec::new();
-
-    // Add loader
-    let loader_path = libraries_dir.join(maven_to_path(&fabric_info.loader.maven));
-    if loader_path.exists() {
-        classpath.push((fabric_info.loader.maven.clone(), loader_path.to_string_lossy().to_string()));
-    }
-
-    // Add intermediary
-    let intermediary_path = libraries_dir.join(maven_to_path(&fabric_info.intermediary.maven));
-    if intermediary_path.exists() {
-        classpath.push((fabric_info.intermediary.maven.clone(), intermediary_path.to_string_lossy().to_string()));
-    }
-
-    // Add common libraries
-    for lib in &fabric_info.launcher_meta.libraries.common {
-        let lib_path = libraries_dir.join(maven_to_path(&lib.name));
-        if lib_path.exists() {
-            classpath.push((lib.name.clone(), lib_path.to_string_lossy().to_string()));
-        }
-    }
-
-    // Add client libraries
-    for lib in &fabric_info.launcher_meta.libraries.client {
-        let lib_path = libraries_dir.join(maven_to_path(&lib.name));
-        if lib_path.exists() {
-            classpath.push((lib.name.clone(), lib_path.to_string_lossy().to_string()));
-        }
+/// Build an inheritsFrom version JSON for the installed loader so it launches through the version resolver
+pub fn fabric_version_json(fabric_info: &FabricLoaderVersion, mc_version: &str) -> serde_json::Value {
+    let mut libraries = vec![
+        serde_json::json!({ "name": fabric_info.loader.maven, "url": "https://maven.fabricmc.net/" }),
+        serde_json::json!({ "name": fabric_info.intermediary.maven, "url": "https://maven.fabricmc.net/" }),
+    ];
+    let meta_libraries = &fabric_info.launcher_meta.libraries;
+    for lib in meta_libraries.common.iter().chain(meta_libraries.client.iter()) {
+        libraries.push(serde_json::json!({ "name": lib.name, "url": lib.url }));
     }
 
-    classpath
+    serde_json::json!({
+        "id": format!("fabric-loader-{}-{}", fabric_info.loader.version, mc_version),
+        "inheritsFrom": mc_version,
+        "mainClass": fabric_info.launcher_meta.main_class.get_client_class(),
+        "libraries": libraries,
+    })
 }
@@ -9,6 +9,7 @@ use flate2::Compression;
 use std::io::Write;
 
 use crate::minecraft::instances::Instance;
+use crate::minecraft::pack_format::{self, PackFormatInfo, PackKind};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct InstalledMod {
@@ -59,6 +60,8 @@ pub struct ResourcePack {
     pub size: u64,
     #[serde(default)]
     pub provider: String,
+    #[serde(default)]
+    pub format: Option<PackFormatInfo>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -96,6 +99,8 @@ pub struct Datapack {
     pub size: u64,
     #[serde(default)]
     pub provider: String,
+    #[serde(default)]
+    pub format: Option<PackFormatInfo>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -200,6 +205,15 @@ pub fn list_mods(instance: &Instance) -> Vec<InstalledMod> {
                         }
                     }
                 }
+
+                // Fall back to the mod's own manifest for manually added jars
+                if provider == "Manual" {
+                    if let Some(manifest) = read_mod_manifest(&path) {
+                        if manifest.name.is_some() { name = manifest.name; }
+                        author = manifest.author;
+                        version = manifest.version;
+                    }
+                }
                 
                 mods.push(InstalledMod {
                     filename: filename.clone(),
@@ -221,6 +235,56 @@ pub fn list_mods(instance: &Instance) -> Vec<InstalledMod> {
     mods
 }
 
+struct ModManifest {
+    name: Option<String>,
+    version: Option<String>,
+    author: Option<String>,
+}
+
+/// Read name/version/author from quilt.mod.json, or fabric.mod.json (Quilt loads both)
+fn read_mod_manifest(jar_path: &Path) -> Option<ModManifest> {
+    let file = File::open(jar_path).ok()?;
+    let mut archive = zip::ZipArchive::new(file).ok()?;
+
+    let read_entry = |archive: &mut zip::ZipArchive<File>, name: &str| -> Option<serde_json::Value> {
+        let mut entry = archive.by_name(name).ok()?;
+        let mut content = String::new();
+        entry.read_to_string(&mut content).ok()?;
+        serde_json::from_str(&content).ok()
+    };
+
+    if let Some(json) = read_entry(&mut archive, "quilt.mod.json") {
+        let loader = json.get("quilt_loader")?;
+        let metadata = loader.get("metadata");
+        // contributors is a map of name -> role
+        let author = metadata
+            .and_then(|m| m.get("contributors"))
+            .and_then(|c| c.as_object())
+            .and_then(|c| c.keys().next().cloned());
+        return Some(ModManifest {
+            name: metadata.and_then(|m| m.get("name")).and_then(|n| n.as_str()).map(|s| s.to_string()),
+            version: loader.get("version").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            author,
+        });
+    }
+
+    if let Some(json) = read_entry(&mut archive, "fabric.mod.json") {
+        // authors entries are either plain strings or { "name": ... } objects
+        let author = json.get("authors")
+            .and_then(|a| a.as_array())
+            .and_then(|a| a.first())
+            .and_then(|a| a.as_str().or_else(|| a.get("name").and_then(|n| n.as_str())))
+            .map(|s| s.to_string());
+        return Some(ModManifest {
+            name: json.get("name").and_then(|n| n.as_str()).map(|s| s.to_string()),
+            version: json.get("version").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            author,
+        });
+    }
+
+    None
+}
+
 /// Toggle mod enabled/disabled
 pub fn toggle_mod(instance: &Instance, filename: &str) -> Result<bool, String> {
     let mods_dir = get_mods_dir(instance);
@@ -305,6 +369,8 @@ pub fn list_resourcepacks(instance: &Instance) -> Vec<ResourcePack> {
                     }
                 }
 
+                let format = pack_format::check_pack(&path, PackKind::Resource, &instance.version_id, None);
+
                 packs.push(ResourcePack {
                     filename: filename.clone(),
                     name,
@@ -315,6 +381,7 @@ pub fn list_resourcepacks(instance: &Instance) -> Vec<ResourcePack> {
                     icon_url,
                     size,
                     provider,
+                    format: Some(format),
                 });
             }
         }
@@ -525,6 +592,21 @@ struct LevelDataTag {
     game_type: Option<i32>,
 }
 
+/// Read the world's display name (LevelName) from its level.dat
+pub fn read_world_level_name(world_path: &Path) -> Option<String> {
+    let data = fs::read(world_path.join("level.dat")).ok()?;
+
+    let mut decoder = GzDecoder::new(&data[..]);
+    let mut decoded = Vec::new();
+    if decoder.read_to_end(&mut decoded).is_err() {
+        // If not gzipped, try raw
+        decoded = data;
+    }
+
+    let nbt = fastnbt::from_bytes::<LevelDatNbt>(&decoded).ok()?;
+    nbt.data?.level_name
+}
+
 fn get_dir_size(path: &Path) -> std::io::Result<u64> {
     let mut size = 0;
     if path.is_dir() {
@@ -553,6 +635,129 @@ pub fn delete_world(instance: &Instance, folder_name: &str) -> Result<(), String
     Ok(())
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportedWorld {
+    pub folder_name: String,
+    pub name: String,
+    pub size: u64,
+}
+
+/// Import a world from a zip archive.
+/// The archive may have level.dat at its root or inside one or more folders;
+/// the shallowest directory containing level.dat is treated as the world root.
+pub fn import_world_zip(instance: &Instance, zip_path: &Path) -> Result<ImportedWorld, String> {
+    let file = File::open(zip_path).map_err(|e| format!("Failed to open zip file: {}", e))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read zip archive: {}", e))?;
+
+    // Locate the world root by its level.dat
+    let mut world_root: Option<PathBuf> = None;
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        let entry_path = match entry.enclosed_name() {
+            Some(p) => p,
+            None => continue,
+        };
+        if entry.is_dir() || entry_path.file_name().map_or(true, |n| n != "level.dat") {
+            continue;
+        }
+        // Skip macOS resource fork copies
+        if entry_path.starts_with("__MACOSX") {
+            continue;
+        }
+        let parent = entry_path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let is_shallower = world_root
+            .as_ref()
+            .map_or(true, |current| parent.components().count() < current.components().count());
+        if is_shallower {
+            world_root = Some(parent);
+        }
+    }
+    let world_root = world_root.ok_or("No level.dat found in archive - this doesn't look like a world")?;
+
+    // Name the folder after the root directory, or the zip itself for root-level worlds
+    let base_name = world_root
+        .file_name()
+        .or_else(|| zip_path.file_stem())
+        .map(|n| n.to_string_lossy().to_string())
+        .filter(|n| !n.trim().is_empty() && !n.starts_with('.'))
+        .unwrap_or_else(|| "Imported World".to_string());
+
+    let saves_dir = get_saves_dir(instance);
+    fs::create_dir_all(&saves_dir).map_err(|e| e.to_string())?;
+
+    let mut folder_name = base_name.clone();
+    let mut suffix = 2;
+    while saves_dir.join(&folder_name).exists() {
+        folder_name = format!("{} ({})", base_name, suffix);
+        suffix += 1;
+    }
+
+    // Extract into a staging folder first so a failed import never leaves a half world behind
+    let staging_path = saves_dir.join(format!(".{}.importing", folder_name));
+    if staging_path.exists() {
+        fs::remove_dir_all(&staging_path).map_err(|e| e.to_string())?;
+    }
+    if let Err(e) = extract_world_entries(&mut archive, &world_root, &staging_path) {
+        let _ = fs::remove_dir_all(&staging_path);
+        return Err(e);
+    }
+
+    let world_path = saves_dir.join(&folder_name);
+    fs::rename(&staging_path, &world_path).map_err(|e| {
+        let _ = fs::remove_dir_all(&staging_path);
+        format!("Failed to move imported world into place: {}", e)
+    })?;
+
+    let name = read_world_level_name(&world_path).unwrap_or_else(|| folder_name.clone());
+    let size = get_dir_size(&world_path).unwrap_or(0);
+
+    log::info!("Imported world '{}' into {}", name, world_path.display());
+    Ok(ImportedWorld { folder_name, name, size })
+}
+
+/// Extract the entries under `world_root` in the archive into `dest`
+pub fn extract_world_entries(
+    archive: &mut zip::ZipArchive<File>,
+    world_root: &Path,
+    dest: &Path,
+) -> Result<(), String> {
+    fs::create_dir_all(dest).map_err(|e| e.to_string())?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+
+        // enclosed_name rejects absolute paths and ".." components
+        let entry_path = match entry.enclosed_name() {
+            Some(p) => p,
+            None => continue,
+        };
+        // Resource fork copies end up next to the world when it sits at the archive root
+        if entry_path.starts_with("__MACOSX") {
+            continue;
+        }
+        let relative = match entry_path.strip_prefix(world_root) {
+            Ok(r) if !r.as_os_str().is_empty() => r.to_path_buf(),
+            _ => continue,
+        };
+
+        let out_path = dest.join(&relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&out_path).map_err(|e| e.to_string())?;
+        } else {
+            if let Some(parent) = out_path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let mut out_file = File::create(&out_path)
+                .map_err(|e| format!("Failed to create file {}: {}", out_path.display(), e))?;
+            std::io::copy(&mut entry, &mut out_file)
+                .map_err(|e| format!("Failed to extract {}: {}", relative.display(), e))?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Rename a world folder
 pub fn rename_world(instance: &Instance, old_name: &str, new_name: &str) -> Result<(), String> {
     let saves_dir = get_saves_dir(instance);
@@ -582,7 +787,19 @@ pub fn update_world_level_name(world_path: &Path, new_name: &str) -> Result<(),
         return Ok(());
     }
 
-    let data = fs::read(&level_dat).map_err(|e| e.to_string())?;
+    let mut nbt = read_level_dat_value(&level_dat)?;
+
+    // NBT structure: Root Compound -> "Data" Compound -> "LevelName" String
+    if let Some(data_tag) = level_data_compound_mut(&mut nbt) {
+        data_tag.insert("LevelName".to_string(), fastnbt::Value::String(new_name.to_string()));
+    }
+
+    write_level_dat_value(&level_dat, &nbt)
+}
+
+/// Read level.dat as a generic NBT value (gzip-compressed or raw)
+fn read_level_dat_value(level_dat: &Path) -> Result<fastnbt::Value, String> {
+    let data = fs::read(level_dat).map_err(|e| e.to_string())?;
     
     // Try to decode
     let mut decoder = GzDecoder::new(&data[..]);
@@ -592,30 +809,371 @@ pub fn update_world_level_name(world_path: &Path, new_name: &str) -> Result<(),
         decoded = data;
     }
 
-    let mut nbt: fastnbt::Value = fastnbt::from_bytes(&decoded).map_err(|e| e.to_string())?;
-
-    // NBT structure: Root Compound -> "Data" Compound -> "LevelName" String
-    if let fastnbt::Value::Compound(root) = &mut nbt {
-        if let Some(fastnbt::Value::Compound(data_tag)) = root.get_mut("Data") {
-            data_tag.insert("LevelName".to_string(), fastnbt::Value::String(new_name.to_string()));
-        } else if let Some(fastnbt::Value::Compound(data_tag)) = root.get_mut("") {
-            // Some versions/parsers might have an empty string root
-             if let Some(fastnbt::Value::Compound(inner_data)) = data_tag.get_mut("Data") {
-                inner_data.insert("LevelName".to_string(), fastnbt::Value::String(new_name.to_string()));
-             }
-        }
-    }
+    fastnbt::from_bytes(&decoded).map_err(|e| e.to_string())
+}
 
-    let new_nbt_bytes = fastnbt::to_bytes(&nbt).map_err(|e| e.to_string())?;
+/// Write a level.dat NBT value back, gzip-compressed as the game expects
+fn write_level_dat_value(level_dat: &Path, nbt: &fastnbt::Value) -> Result<(), String> {
+    let new_nbt_bytes = fastnbt::to_bytes(nbt).map_err(|e| e.to_string())?;
 
     // Re-compress
     let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
     encoder.write_all(&new_nbt_bytes).map_err(|e| e.to_string())?;
     let compressed = encoder.finish().map_err(|e| e.to_string())?;
 
-    fs::write(level_dat, compressed).map_err(|e| e.to_string())?;
+    fs::write(level_dat, compressed).map_err(|e| e.to_string())
+}
+
+/// Find the "Data" compound inside a level.dat root
+fn level_data_compound_mut(nbt: &mut fastnbt::Value) -> Option<&mut std::collections::HashMap<String, fastnbt::Value>> {
+    let root = match nbt {
+        fastnbt::Value::Compound(root) => root,
+        _ => return None,
+    };
 
-    Ok(())
+    // Some versions/parsers might have an empty string root
+    let parent = if !root.contains_key("Data") && matches!(root.get(""), Some(fastnbt::Value::Compound(_))) {
+        match root.get_mut("") {
+            Some(fastnbt::Value::Compound(inner)) => inner,
+            _ => return None,
+        }
+    } else {
+        root
+    };
+
+    match parent.get_mut("Data") {
+        Some(fastnbt::Value::Compound(data_tag)) => Some(data_tag),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorldDetails {
+    pub folder_name: String,
+    pub name: String,
+    pub seed: Option<i64>,
+    pub game_mode: Option<i32>,
+    pub difficulty: Option<i8>,
+    pub difficulty_locked: bool,
+    pub hardcore: bool,
+    pub allow_commands: bool,
+    pub spawn_x: Option<i32>,
+    pub spawn_y: Option<i32>,
+    pub spawn_z: Option<i32>,
+    pub day_time: Option<i64>,
+    pub time: Option<i64>,
+    pub version_name: Option<String>,
+    pub data_version: Option<i32>,
+    pub snapshot: bool,
+    pub enabled_datapacks: Vec<String>,
+    pub disabled_datapacks: Vec<String>,
+    pub gamerules: std::collections::BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LevelDetailsNbt {
+    #[serde(rename = "Data")]
+    data: Option<LevelDetailsTag>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LevelDetailsTag {
+    #[serde(rename = "LevelName")]
+    level_name: Option<String>,
+    #[serde(rename = "GameType")]
+    game_type: Option<i32>,
+    // Pre-1.16 worlds keep the seed here, newer ones in WorldGenSettings
+    #[serde(rename = "RandomSeed")]
+    random_seed: Option<i64>,
+    #[serde(rename = "WorldGenSettings")]
+    world_gen_settings: Option<WorldGenSettingsTag>,
+    #[serde(rename = "Difficulty")]
+    difficulty: Option<i8>,
+    #[serde(rename = "DifficultyLocked")]
+    difficulty_locked: Option<i8>,
+    hardcore: Option<i8>,
+    #[serde(rename = "allowCommands")]
+    allow_commands: Option<i8>,
+    #[serde(rename = "SpawnX")]
+    spawn_x: Option<i32>,
+    #[serde(rename = "SpawnY")]
+    spawn_y: Option<i32>,
+    #[serde(rename = "SpawnZ")]
+    spawn_z: Option<i32>,
+    #[serde(rename = "DayTime")]
+    day_time: Option<i64>,
+    #[serde(rename = "Time")]
+    time: Option<i64>,
+    #[serde(rename = "Version")]
+    version: Option<LevelVersionTag>,
+    #[serde(rename = "DataVersion")]
+    data_version: Option<i32>,
+    #[serde(rename = "DataPacks")]
+    data_packs: Option<LevelDataPacksTag>,
+    #[serde(rename = "GameRules")]
+    game_rules: Option<std::collections::HashMap<String, fastnbt::Value>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorldGenSettingsTag {
+    seed: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LevelVersionTag {
+    #[serde(rename = "Name")]
+    name: Option<String>,
+    #[serde(rename = "Id")]
+    id: Option<i32>,
+    #[serde(rename = "Snapshot")]
+    snapshot: Option<i8>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LevelDataPacksTag {
+    #[serde(rename = "Enabled", default)]
+    enabled: Vec<String>,
+    #[serde(rename = "Disabled", default)]
+    disabled: Vec<String>,
+}
+
+/// Gamerules are strings in most versions, but newer ones store typed values
+fn gamerule_to_string(value: &fastnbt::Value) -> Option<String> {
+    match value {
+        fastnbt::Value::String(s) => Some(s.clone()),
+        fastnbt::Value::Byte(b) => Some((*b != 0).to_string()),
+        fastnbt::Value::Short(v) => Some(v.to_string()),
+        fastnbt::Value::Int(v) => Some(v.to_string()),
+        fastnbt::Value::Long(v) => Some(v.to_string()),
+        _ => None,
+    }
+}
+
+/// Read detailed world information from level.dat
+pub fn get_world_details(instance: &Instance, folder_name: &str) -> Result<WorldDetails, String> {
+    let world_path = get_saves_dir(instance).join(folder_name);
+    let level_dat = world_path.join("level.dat");
+    if !level_dat.exists() {
+        return Err("World folder not found".to_string());
+    }
+
+    let data = fs::read(&level_dat).map_err(|e| e.to_string())?;
+    let mut decoder = GzDecoder::new(&data[..]);
+    let mut decoded = Vec::new();
+    if decoder.read_to_end(&mut decoded).is_err() {
+        decoded = data;
+    }
+
+    let nbt: LevelDetailsNbt = fastnbt::from_bytes(&decoded)
+        .map_err(|e| format!("Failed to parse level.dat: {}", e))?;
+    let tag = nbt.data.ok_or("level.dat has no Data compound")?;
+
+    let seed = tag.world_gen_settings.and_then(|w| w.seed).or(tag.random_seed);
+    let (version_name, version_id, snapshot) = match tag.version {
+        Some(v) => (v.name, v.id, v.snapshot.unwrap_or(0) != 0),
+        None => (None, None, false),
+    };
+    let (enabled_datapacks, disabled_datapacks) = match tag.data_packs {
+        Some(packs) => (packs.enabled, packs.disabled),
+        None => (Vec::new(), Vec::new()),
+    };
+    let gamerules = tag.game_rules
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|(k, v)| gamerule_to_string(v).map(|s| (k.clone(), s)))
+        .collect();
+
+    Ok(WorldDetails {
+        folder_name: folder_name.to_string(),
+        name: tag.level_name.unwrap_or_else(|| folder_name.to_string()),
+        seed,
+        game_mode: tag.game_type,
+        difficulty: tag.difficulty,
+        difficulty_locked: tag.difficulty_locked.unwrap_or(0) != 0,
+        hardcore: tag.hardcore.unwrap_or(0) != 0,
+        allow_commands: tag.allow_commands.unwrap_or(0) != 0,
+        spawn_x: tag.spawn_x,
+        spawn_y: tag.spawn_y,
+        spawn_z: tag.spawn_z,
+        day_time: tag.day_time,
+        time: tag.time,
+        version_name,
+        data_version: tag.data_version.or(version_id),
+        snapshot,
+        enabled_datapacks,
+        disabled_datapacks,
+        gamerules,
+    })
+}
+
+/// Fields of level.dat that are safe to edit from the launcher
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WorldSettingsUpdate {
+    pub difficulty: Option<i8>,
+    pub game_mode: Option<i32>,
+    pub allow_commands: Option<bool>,
+    pub day_time: Option<i64>,
+    pub gamerules: Option<std::collections::HashMap<String, String>>,
+}
+
+/// Validate and apply edits to level.dat. The original file is copied aside first.
+/// Returns the backup file name.
+pub fn update_world_settings(instance: &Instance, folder_name: &str, update: &WorldSettingsUpdate) -> Result<String, String> {
+    use fastnbt::Value;
+
+    let world_path = get_saves_dir(instance).join(folder_name);
+    let level_dat = world_path.join("level.dat");
+    if !level_dat.exists() {
+        return Err("World folder not found".to_string());
+    }
+
+    let mut nbt = read_level_dat_value(&level_dat)?;
+    let data_tag = level_data_compound_mut(&mut nbt).ok_or("level.dat has no Data compound")?;
+
+    let is_hardcore = matches!(data_tag.get("hardcore"), Some(Value::Byte(b)) if *b != 0);
+
+    if let Some(difficulty) = update.difficulty {
+        if !(0..=3).contains(&difficulty) {
+            return Err("Difficulty must be between 0 (peaceful) and 3 (hard)".to_string());
+        }
+        if is_hardcore && difficulty != 3 {
+            return Err("Hardcore worlds are always on hard difficulty".to_string());
+        }
+        data_tag.insert("Difficulty".to_string(), Value::Byte(difficulty));
+    }
+
+    if let Some(game_mode) = update.game_mode {
+        if !(0..=3).contains(&game_mode) {
+            return Err("Game mode must be between 0 (survival) and 3 (spectator)".to_string());
+        }
+        if is_hardcore {
+            return Err("The game mode of a hardcore world can't be changed".to_string());
+        }
+        data_tag.insert("GameType".to_string(), Value::Int(game_mode));
+        // Singleplayer worlds also store the player's own mode
+        if let Some(Value::Compound(player)) = data_tag.get_mut("Player") {
+            player.insert("playerGameType".to_string(), Value::Int(game_mode));
+        }
+    }
+
+    if let Some(allow_commands) = update.allow_commands {
+        data_tag.insert("allowCommands".to_string(), Value::Byte(allow_commands as i8));
+    }
+
+    if let Some(day_time) = update.day_time {
+        if day_time < 0 {
+            return Err("Time of day can't be negative".to_string());
+        }
+        data_tag.insert("DayTime".to_string(), Value::Long(day_time));
+    }
+
+    if let Some(gamerules) = &update.gamerules {
+        let rules = match data_tag.get_mut("GameRules") {
+            Some(Value::Compound(rules)) => rules,
+            _ => return Err("This world has no gamerules to edit".to_string()),
+        };
+
+        for (key, new_value) in gamerules {
+            // Only existing rules can be changed, and the value must keep its type
+            let current = rules.get(key)
+                .ok_or_else(|| format!("Unknown gamerule: {}", key))?;
+            let current_str = gamerule_to_string(current).unwrap_or_default();
+            let is_bool = current_str == "true" || current_str == "false";
+
+            let validated = if is_bool {
+                match new_value.as_str() {
+                    "true" | "false" => new_value.clone(),
+                    _ => return Err(format!("Gamerule {} must be true or false", key)),
+                }
+            } else {
+                new_value.trim().parse::<i32>()
+                    .map_err(|_| format!("Gamerule {} must be a whole number", key))?
+                    .to_string()
+            };
+
+            let replacement = match current {
+                Value::String(_) => Value::String(validated),
+                Value::Byte(_) => Value::Byte((validated == "true") as i8),
+                Value::Long(_) => Value::Long(validated.parse().unwrap_or(0)),
+                _ => Value::Int(validated.parse().unwrap_or(0)),
+            };
+            rules.insert(key.clone(), replacement);
+        }
+    }
+
+    // Keep a copy of the original before writing anything
+    let backup_name = backup_level_dat(&level_dat, &world_path)?;
+    prune_level_dat_backups(&world_path);
+
+    write_level_dat_value(&level_dat, &nbt)?;
+
+    Ok(backup_name)
+}
+
+/// level.dat backups kept in a world folder by world settings edits
+const LEVEL_DAT_BACKUPS_KEPT: usize = 10;
+
+/// Copy level.dat to a new timestamped .bak file, never replacing an earlier backup made
+/// within the same second
+fn backup_level_dat(level_dat: &Path, world_path: &Path) -> Result<String, String> {
+    let stamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
+    let mut source = File::open(level_dat)
+        .map_err(|e| format!("Failed to back up level.dat: {}", e))?;
+
+    for attempt in 0..100 {
+        let backup_name = match attempt {
+            0 => format!("level.dat.{}.bak", stamp),
+            n => format!("level.dat.{}_{}.bak", stamp, n),
+        };
+        let mut backup = match fs::OpenOptions::new().write(true).create_new(true).open(world_path.join(&backup_name)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("Failed to back up level.dat: {}", e)),
+        };
+        std::io::copy(&mut source, &mut backup)
+            .and_then(|_| backup.sync_all())
+            .map_err(|e| format!("Failed to back up level.dat: {}", e))?;
+        return Ok(backup_name);
+    }
+    Err("Failed to back up level.dat: too many backups this second".to_string())
+}
+
+/// Remove the oldest level.dat backups beyond LEVEL_DAT_BACKUPS_KEPT
+fn prune_level_dat_backups(world_path: &Path) {
+    let Ok(entries) = fs::read_dir(world_path) else { return };
+    let mut backups: Vec<(std::time::SystemTime, String, u32, PathBuf)> = entries.flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let stamp = name.strip_prefix("level.dat.")?.strip_suffix(".bak")?;
+            // Backups made within the same second share a modified time, the counter orders them
+            let (stamp, counter) = match stamp.rsplit_once('_') {
+                Some((base, n)) if n.len() < 4 && n.chars().all(|c| c.is_ascii_digit()) => (base, n.parse().unwrap_or(0)),
+                _ => (stamp, 0),
+            };
+            let modified = e.metadata().and_then(|m| m.modified()).unwrap_or(std::time::UNIX_EPOCH);
+            Some((modified, stamp.to_string(), counter, e.path()))
+        })
+        .collect();
+    if backups.len() <= LEVEL_DAT_BACKUPS_KEPT {
+        return;
+    }
+
+    // Newest first
+    backups.sort_by(|a, b| b.cmp(a));
+    for (_, _, _, path) in backups.into_iter().skip(LEVEL_DAT_BACKUPS_KEPT) {
+        if let Err(e) = fs::remove_file(&path) {
+            log::warn!("Failed to remove old level.dat backup {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Read the DataVersion a world was last saved with
+pub fn read_world_data_version(world_path: &Path) -> Option<i32> {
+    let mut nbt = read_level_dat_value(&world_path.join("level.dat")).ok()?;
+    match level_data_compound_mut(&mut nbt)?.get("DataVersion") {
+        Some(fastnbt::Value::Int(v)) => Some(*v),
+        _ => None,
+    }
 }
 
 /// List datapacks for a world
@@ -626,6 +1184,8 @@ pub fn list_datapacks(instance: &Instance, world_name: &str) -> Vec<Datapack> {
     if !datapacks_dir.exists() {
         return datapacks;
     }
+
+    let world_data_version = read_world_data_version(&get_saves_dir(instance).join(world_name));
     
     if let Ok(entries) = fs::read_dir(&datapacks_dir) {
         for entry in entries.flatten() {
@@ -664,6 +1224,8 @@ pub fn list_datapacks(instance: &Instance, world_name: &str) -> Vec<Datapack> {
                     }
                 }
 
+                let format = pack_format::check_pack(&path, PackKind::Data, &instance.version_id, world_data_version);
+
                 datapacks.push(Datapack {
                     filename: filename.clone(),
                     name,
@@ -675,6 +1237,7 @@ pub fn list_datapacks(instance: &Instance, world_name: &str) -> Vec<Datapack> {
                     size,
                     provider,
                     enabled: true,
+                    format: Some(format),
                 });
             }
         }
@@ -909,3 +1472,57 @@ pub fn open_path(path: &PathBuf) -> Result<(), String> {
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_world() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("palethea-world-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("level.dat"), b"original").unwrap();
+        dir
+    }
+
+    fn backups(world: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(world).unwrap().flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|n| n.starts_with("level.dat.") && n.ends_with(".bak"))
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn backups_in_the_same_second_are_kept() {
+        let world = temp_world();
+        let level_dat = world.join("level.dat");
+
+        let first = backup_level_dat(&level_dat, &world).unwrap();
+        fs::write(&level_dat, b"edited").unwrap();
+        let second = backup_level_dat(&level_dat, &world).unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(fs::read(world.join(&first)).unwrap(), b"original");
+        assert_eq!(fs::read(world.join(&second)).unwrap(), b"edited");
+        fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn old_backups_are_pruned() {
+        let world = temp_world();
+        let level_dat = world.join("level.dat");
+        for _ in 0..LEVEL_DAT_BACKUPS_KEPT + 3 {
+            backup_level_dat(&level_dat, &world).unwrap();
+        }
+        let newest = backup_level_dat(&level_dat, &world).unwrap();
+
+        prune_level_dat_backups(&world);
+
+        let kept = backups(&world);
+        assert_eq!(kept.len(), LEVEL_DAT_BACKUPS_KEPT);
+        assert!(kept.contains(&newest));
+        assert!(world.join("level.dat").exists());
+        fs::remove_dir_all(&world).unwrap();
+    }
+}
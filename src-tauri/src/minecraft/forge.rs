@@ -1,10 +1,15 @@
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::fs;
-use std::process::Command;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
 
 use crate::minecraft::instances::Instance;
 use crate::minecraft::launcher;
+use crate::minecraft::versions::{self, VersionDetails};
 
 // ----------
 // Windows console hiding
@@ -31,10 +36,126 @@ pub struct ForgeLibrary {
     pub url: Option<String>,
 }
 
+/// One line of installer output, emitted as "loader-install-progress"
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InstallerProgress {
+    pub instance_id: String,
+    pub loader: String,
+    pub line: String,
+}
+
+/// Lines of installer output kept for the error message when it fails
+const INSTALLER_TAIL_LINES: usize = 20;
+
+struct InstallerOutcome {
+    status: ExitStatus,
+    tail: Vec<String>,
+}
+
+/// Details of the instance's game version, read from disk or fetched if it isn't installed yet
+async fn target_version_details(version_id: &str) -> Result<VersionDetails, Box<dyn Error + Send + Sync>> {
+    if let Ok(details) = versions::resolve_version(version_id) {
+        return Ok(details);
+    }
+    let manifest = versions::fetch_version_manifest().await?;
+    let info = manifest.versions.iter()
+        .find(|v| v.id == version_id)
+        .ok_or_else(|| format!("Minecraft version {} not found", version_id))?;
+    versions::fetch_version_details(&info.url).await
+}
+
+/// Run an installer jar with --installClient, streaming each line of its output as it's printed
+fn run_installer(
+    loader: &str,
+    instance_id: &str,
+    java_path: &Path,
+    installer_path: &Path,
+    minecraft_dir: &Path,
+    app_handle: Option<&AppHandle>,
+) -> Result<InstallerOutcome, String> {
+    let mut command = Command::new(java_path);
+    command.arg("-jar").arg(installer_path);
+    command.arg("--installClient").arg(minecraft_dir);
+    command.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    #[cfg(target_os = "windows")]
+    command.creation_flags(CREATE_NO_WINDOW);
+
+    let mut child = command.spawn()
+        .map_err(|e| format!("Failed to run {} installer: {}", loader, e))?;
+
+    let tail = Arc::new(Mutex::new(Vec::new()));
+    let forward = |stream: Box<dyn std::io::Read + Send>| {
+        let tail = Arc::clone(&tail);
+        let app_handle = app_handle.cloned();
+        let loader = loader.to_string();
+        let instance_id = instance_id.to_string();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                log::info!("[{} installer] {}", loader, line);
+                if let Some(handle) = &app_handle {
+                    let _ = handle.emit("loader-install-progress", InstallerProgress {
+                        instance_id: instance_id.clone(),
+                        loader: loader.clone(),
+                        line: line.clone(),
+                    });
+                }
+                if let Ok(mut tail) = tail.lock() {
+                    tail.push(line);
+                    if tail.len() > INSTALLER_TAIL_LINES {
+                        tail.remove(0);
+                    }
+                }
+            }
+        })
+    };
+
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(forward(Box::new(stdout)));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(forward(Box::new(stderr)));
+    }
+
+    let status = child.wait()
+        .map_err(|e| format!("Failed to wait for {} installer: {}", loader, e))?;
+    for reader in readers {
+        let _ = reader.join();
+    }
+
+    let tail = tail.lock().map(|t| t.clone()).unwrap_or_default();
+    Ok(InstallerOutcome { status, tail })
+}
+
+/// Pick Java for the installer and run it on a blocking thread
+async fn run_installer_for(
+    loader: &str,
+    instance: &Instance,
+    installer_path: &PathBuf,
+    minecraft_dir: &PathBuf,
+    app_handle: Option<&AppHandle>,
+) -> Result<InstallerOutcome, Box<dyn Error + Send + Sync>> {
+    let version_details = target_version_details(&instance.version_id).await?;
+    let java_path = launcher::select_java_for_installer(instance, &version_details, app_handle).await?;
+    log::info!("Running {} installer with {}", loader, java_path.display());
+
+    let loader = loader.to_string();
+    let instance_id = instance.id.clone();
+    let installer_path = installer_path.clone();
+    let minecraft_dir = minecraft_dir.clone();
+    let app_handle = app_handle.cloned();
+    let outcome = tokio::task::spawn_blocking(move || {
+        run_installer(&loader, &instance_id, &java_path, &installer_path, &minecraft_dir, app_handle.as_ref())
+    }).await??;
+    Ok(outcome)
+}
+
 /// Download Forge installer and run it
 pub async fn install_forge(
     instance: &Instance,
     forge_version: &str,
+    app_handle: Option<&AppHandle>,
 ) -> Result<ForgeVersionInfo, Box<dyn Error + Send + Sync>> {
     let mc_version = &instance.version_id;
     // Forge URL patterns are nightmare. Older versions (like 1.8.9) often use {mc}-{forge}-{mc}
@@ -85,10 +206,6 @@ pub async fn install_forge(
     log::info!("Downloaded Forge installer ({} bytes)", bytes.len());
     fs::write(&installer_path, &bytes)?;
     
-    // Find Java
-    let java_path = launcher::find_java()
-        .ok_or("Java not found. Please install Java to install Forge.")?;
-    
     let minecraft_dir = crate::minecraft::downloader::get_minecraft_dir();
     
     // Ensure launcher_profiles.json exists (Forge installer requirement)
@@ -97,26 +214,12 @@ pub async fn install_forge(
         let _ = fs::write(&profiles_path, "{\"profiles\":{}}");
     }
     
-    // Run installer in client mode
-    let forge_cmd_str = format!("\"{}\" -jar \"{}\" --installClient \"{}\"", java_path.display(), installer_path.display(), minecraft_dir.display());
-    println!("\n[DEBUG] Running Forge installer:\n{}\n", forge_cmd_str);
-    log::info!("Running Forge installer: {}", forge_cmd_str);
-    
     // Some older Forge installers use different arguments or require a different display mode
     // but --installClient is standard for modern ones. 
     // For 1.8.9 specifically, it often needs the path to be the .minecraft root.
+    let outcome = run_installer_for("Forge", instance, &installer_path, &minecraft_dir, app_handle).await?;
     
-    let mut command = Command::new(&java_path);
-    command.arg("-jar").arg(&installer_path);
-    command.arg("--installClient").arg(&minecraft_dir);
-    
-    #[cfg(target_os = "windows")]
-    command.creation_flags(CREATE_NO_WINDOW);
-    
-    let output = command.output()
-        .map_err(|e| format!("Failed to run Forge installer: {}", e))?;
-    
-    if !output.status.success() {
+    if !outcome.status.success() {
         // Fallback for older installers (like 1.8.9) that don't support --installClient
         log::info!("Standard Forge installer failed, attempting manual extraction for legacy version...");
         let version_id = handle_legacy_forge_installer(&installer_path, mc_version, forge_version)?;
@@ -144,6 +247,8 @@ pub async fn install_forge(
         log::info!("Forge installer completed successfully.");
     }
     
+    let version_id = installed_version_id(&installer_path);
+    
     // Clean up installer
     let _ = fs::remove_file(&installer_path);
     
@@ -152,7 +257,7 @@ pub async fn install_forge(
         forge_version: forge_version.to_string(),
         minecraft_version: mc_version.to_string(),
         main_class: String::new(), 
-        version_id: None,
+        version_id,
         libraries: Vec::new(), 
     };
     
@@ -165,6 +270,24 @@ pub async fn install_forge(
     Ok(forge_info)
 }
 
+/// ID of the version a modern installer writes, read from the version.json it carries.
+/// None if the jar has none or that version didn't end up in the versions folder.
+fn installed_version_id(installer_path: &Path) -> Option<String> {
+    let file = fs::File::open(installer_path).ok()?;
+    let mut archive = zip::ZipArchive::new(file).ok()?;
+    let entry = archive.by_name("version.json").ok()?;
+    let version_json: serde_json::Value = serde_json::from_reader(entry).ok()?;
+    let id = version_json.get("id")?.as_str()?.to_string();
+
+    let versions_dir = crate::minecraft::downloader::get_versions_dir();
+    if !versions_dir.join(&id).join(format!("{}.json", id)).exists() {
+        log::warn!("Installer reported version {} but it isn't in {}", id, versions_dir.display());
+        return None;
+    }
+    log::info!("Installer created version {}", id);
+    Some(id)
+}
+
 /// Handle older Forge installers by manually extracting metadata and JARs
 fn handle_legacy_forge_installer(
     installer_path: &std::path::PathBuf,
@@ -226,6 +349,7 @@ fn handle_legacy_forge_installer(
 pub async fn install_neoforge(
     instance: &Instance,
     neoforge_version: &str,
+    app_handle: Option<&AppHandle>,
 ) -> Result<ForgeVersionInfo, Box<dyn Error + Send + Sync>> {
     let installer_url = format!(
         "https://maven.neoforged.net/releases/net/neoforged/neoforge/{}/neoforge-{}-installer.jar",
@@ -252,10 +376,6 @@ pub async fn install_neoforge(
     let bytes = response.bytes().await?;
     fs::write(&installer_path, &bytes)?;
     
-    // Find Java
-    let java_path = launcher::find_java()
-        .ok_or("Java not found. Please install Java to install NeoForge.")?;
-    
     let minecraft_dir = crate::minecraft::downloader::get_minecraft_dir();
     
     // Ensure launcher_profiles.json exists (NeoForge installer requirement)
@@ -264,32 +384,19 @@ pub async fn install_neoforge(
         let _ = fs::write(&profiles_path, "{\"profiles\":{}}");
     }
     
-    // Run installer in client mode
-    let neoforge_cmd_str = format!("\"{}\" -jar \"{}\" --installClient \"{}\"", java_path.display(), installer_path.display(), minecraft_dir.display());
-    println!("\n[DEBUG] Running NeoForge installer:\n{}\n", neoforge_cmd_str);
-    log::info!("Running NeoForge installer: {}", neoforge_cmd_str);
+    let outcome = run_installer_for("NeoForge", instance, &installer_path, &minecraft_dir, app_handle).await?;
     
-    let mut neoforge_cmd = Command::new(&java_path);
-    neoforge_cmd.args(&[
-        "-jar", 
-        installer_path.to_str().ok_or("Invalid path")?, 
-        "--installClient", 
-        minecraft_dir.to_str().ok_or("Invalid path")?
-    ]);
-    
-    #[cfg(target_os = "windows")]
-    neoforge_cmd.creation_flags(CREATE_NO_WINDOW);
-    
-    let output = neoforge_cmd.output()
-        .map_err(|e| format!("Failed to run NeoForge installer: {}", e))?;
-    
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        log::error!("NeoForge installer failed. Stderr: {}. Stdout: {}", stderr, stdout);
-        return Err(format!("NeoForge installer failed. See logs for details.").into());
+    if !outcome.status.success() {
+        log::error!("NeoForge installer failed ({})", outcome.status);
+        let mut message = format!("NeoForge installer failed ({})", outcome.status);
+        if !outcome.tail.is_empty() {
+            message.push_str(&format!("\n{}", outcome.tail.join("\n")));
+        }
+        return Err(message.into());
     }
     
+    let version_id = installed_version_id(&installer_path);
+    
     // Clean up installer
     let _ = fs::remove_file(&installer_path);
     
@@ -298,7 +405,7 @@ pub async fn install_neoforge(
         forge_version: neoforge_version.to_string(),
         minecraft_version: instance.version_id.to_string(),
         main_class: String::new(),
-        version_id: None,
+        version_id,
         libraries: Vec::new(),
     };
     
@@ -312,7 +419,6 @@ pub async fn install_neoforge(
 }
 
 /// Load saved Forge info from instance
-#[allow(dead_code)]
 pub fn load_forge_info(instance: &Instance) -> Option<ForgeVersionInfo> {
     let forge_json_path = instance.get_directory().join("forge.json");
     if !forge_json_path.exists() {
@@ -324,7 +430,6 @@ pub fn load_forge_info(instance: &Instance) -> Option<ForgeVersionInfo> {
 }
 
 /// Load saved NeoForge info from instance
-#[allow(dead_code)]
 pub fn load_neoforge_info(instance: &Instance) -> Option<ForgeVersionInfo> {
     let neoforge_json_path = instance.get_directory().join("neoforge.json");
     if !neoforge_json_path.exists() {
@@ -0,0 +1,446 @@
+use crate::minecraft::downloader::get_minecraft_dir;
+use crate::minecraft::instances::{Instance, ModLoader};
+use crate::minecraft::jvm;
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Appends from games exiting at the same time go through one writer
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// One finished play session
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaySession {
+    pub id: String,
+    pub instance_id: String,
+    pub instance_name: String,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub duration_seconds: u64,
+    /// None when the process was killed by a signal or re-attached after a launcher restart
+    pub exit_code: Option<i32>,
+    pub crashed: bool,
+    /// Stopped from the launcher with kill_game
+    #[serde(default)]
+    pub killed: bool,
+    /// Picked up again after the launcher itself crashed, the outcome is unknown
+    #[serde(default)]
+    pub recovered: bool,
+    pub account: Option<String>,
+    pub version_id: String,
+    pub mod_loader: ModLoader,
+    pub loader_version: Option<String>,
+    pub mod_count: u32,
+}
+
+impl PlaySession {
+    pub fn new(instance: &Instance, start_time: u64, end_time: u64, mod_count: u32) -> Self {
+        PlaySession {
+            id: Uuid::new_v4().to_string(),
+            instance_id: instance.id.clone(),
+            instance_name: instance.name.clone(),
+            start_time,
+            end_time,
+            duration_seconds: end_time.saturating_sub(start_time),
+            exit_code: None,
+            crashed: false,
+            killed: false,
+            recovered: false,
+            account: None,
+            version_id: instance.version_id.clone(),
+            mod_loader: instance.mod_loader.clone(),
+            loader_version: instance.mod_loader_version.clone(),
+            mod_count,
+        }
+    }
+
+    /// A session the launcher lost track of, credited from its start until it was noticed gone
+    pub fn recovered(instance: &Instance, start_time: u64, end_time: u64) -> Self {
+        let mut session = PlaySession::new(instance, start_time, end_time, jvm::count_mods(instance));
+        session.recovered = true;
+        session
+    }
+
+    /// Whether the exit status is known, recovered sessions don't count towards crash rates
+    fn has_outcome(&self) -> bool {
+        !self.recovered
+    }
+}
+
+fn get_history_path() -> PathBuf {
+    get_minecraft_dir().join("session_history.jsonl")
+}
+
+/// Append a finished session to the history
+pub fn record_session(session: &PlaySession) -> Result<(), String> {
+    let line = serde_json::to_string(session)
+        .map_err(|e| format!("Failed to serialize session: {}", e))?;
+    let _guard = HISTORY_LOCK.lock().map_err(|_| "Session history lock poisoned")?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(get_history_path())
+        .map_err(|e| format!("Failed to open session history: {}", e))?;
+    writeln!(file, "{}", line)
+        .map_err(|e| format!("Failed to write session history: {}", e))
+}
+
+/// Every recorded session, oldest first. Unreadable lines are skipped.
+pub fn load_history() -> Vec<PlaySession> {
+    let Ok(content) = fs::read_to_string(get_history_path()) else { return Vec::new() };
+    let mut sessions: Vec<PlaySession> = content.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    sessions.sort_by_key(|s| s.start_time);
+    sessions
+}
+
+/// Sessions of one instance, or all of them
+fn sessions_for(instance_id: Option<&str>) -> Vec<PlaySession> {
+    load_history()
+        .into_iter()
+        .filter(|s| instance_id.map_or(true, |id| s.instance_id == id))
+        .collect()
+}
+
+/// Newest first, optionally limited to one instance and a number of sessions
+pub fn get_history(instance_id: Option<&str>, limit: Option<usize>) -> Vec<PlaySession> {
+    let mut sessions = sessions_for(instance_id);
+    sessions.reverse();
+    if let Some(limit) = limit {
+        sessions.truncate(limit);
+    }
+    sessions
+}
+
+fn local_date(timestamp: u64) -> NaiveDate {
+    Local.timestamp_opt(timestamp as i64, 0)
+        .earliest()
+        .map(|d| d.date_naive())
+        .unwrap_or_default()
+}
+
+fn local_midnight(date: NaiveDate) -> Option<u64> {
+    let midnight = date.and_hms_opt(0, 0, 0)?;
+    Local.from_local_datetime(&midnight).earliest().map(|d| d.timestamp() as u64)
+}
+
+/// Seconds of a session that fall on each local day, sessions past midnight count for both days
+fn split_by_day(session: &PlaySession) -> Vec<(NaiveDate, u64)> {
+    let mut parts = Vec::new();
+    let mut current = session.start_time;
+    while current < session.end_time {
+        let day = local_date(current);
+        let next = day.succ_opt()
+            .and_then(local_midnight)
+            .filter(|next| *next > current)
+            .unwrap_or(session.end_time);
+        let part_end = next.min(session.end_time);
+        parts.push((day, part_end - current));
+        current = part_end;
+    }
+    parts
+}
+
+fn seconds_by_day(sessions: &[PlaySession]) -> BTreeMap<NaiveDate, u64> {
+    let mut days = BTreeMap::new();
+    for session in sessions {
+        for (day, seconds) in split_by_day(session) {
+            *days.entry(day).or_insert(0) += seconds;
+        }
+    }
+    days
+}
+
+/// Playtime in one day or week
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaytimeBucket {
+    /// "2026-10-18" for days, "2026-W42" for weeks
+    pub period: String,
+    pub start_date: String,
+    pub seconds: u64,
+    /// Sessions started in the period
+    pub sessions: u32,
+}
+
+/// Playtime per local day for the last `days` days, today included, days without play are zero
+pub fn playtime_by_day(days: u32, instance_id: Option<&str>) -> Vec<PlaytimeBucket> {
+    let sessions = sessions_for(instance_id);
+    let seconds = seconds_by_day(&sessions);
+    let mut started: HashMap<NaiveDate, u32> = HashMap::new();
+    for session in &sessions {
+        *started.entry(local_date(session.start_time)).or_insert(0) += 1;
+    }
+
+    let today = Local::now().date_naive();
+    (0..days.max(1) as i64).rev()
+        .map(|offset| {
+            let day = today - Duration::days(offset);
+            PlaytimeBucket {
+                period: day.to_string(),
+                start_date: day.to_string(),
+                seconds: seconds.get(&day).copied().unwrap_or(0),
+                sessions: started.get(&day).copied().unwrap_or(0),
+            }
+        })
+        .collect()
+}
+
+fn week_start(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+/// Playtime per ISO week (Monday to Sunday) for the last `weeks` weeks
+pub fn playtime_by_week(weeks: u32, instance_id: Option<&str>) -> Vec<PlaytimeBucket> {
+    let sessions = sessions_for(instance_id);
+    let mut seconds: HashMap<NaiveDate, u64> = HashMap::new();
+    for (day, day_seconds) in seconds_by_day(&sessions) {
+        *seconds.entry(week_start(day)).or_insert(0) += day_seconds;
+    }
+    let mut started: HashMap<NaiveDate, u32> = HashMap::new();
+    for session in &sessions {
+        *started.entry(week_start(local_date(session.start_time))).or_insert(0) += 1;
+    }
+
+    let this_week = week_start(Local::now().date_naive());
+    (0..weeks.max(1) as i64).rev()
+        .map(|offset| {
+            let start = this_week - Duration::weeks(offset);
+            let iso = start.iso_week();
+            PlaytimeBucket {
+                period: format!("{}-W{:02}", iso.year(), iso.week()),
+                start_date: start.to_string(),
+                seconds: seconds.get(&start).copied().unwrap_or(0),
+                sessions: started.get(&start).copied().unwrap_or(0),
+            }
+        })
+        .collect()
+}
+
+/// Session statistics of one instance
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InstanceSessionStats {
+    pub instance_id: String,
+    pub instance_name: String,
+    pub sessions: u32,
+    pub playtime_seconds: u64,
+    pub average_session_seconds: u64,
+    pub longest_session_seconds: u64,
+    pub crashes: u32,
+    /// Crashes over sessions with a known outcome
+    pub crash_rate: f32,
+    pub last_played: Option<u64>,
+}
+
+fn crash_rate(crashes: u32, known: u32) -> f32 {
+    if known == 0 { 0.0 } else { crashes as f32 / known as f32 }
+}
+
+/// Per-instance playtime, session length and crash rate, most played first
+pub fn instance_stats() -> Vec<InstanceSessionStats> {
+    let mut by_instance: HashMap<String, Vec<PlaySession>> = HashMap::new();
+    for session in load_history() {
+        by_instance.entry(session.instance_id.clone()).or_default().push(session);
+    }
+
+    let mut stats: Vec<InstanceSessionStats> = by_instance.into_values()
+        .map(|sessions| {
+            let playtime: u64 = sessions.iter().map(|s| s.duration_seconds).sum();
+            let crashes = sessions.iter().filter(|s| s.crashed).count() as u32;
+            let known = sessions.iter().filter(|s| s.has_outcome()).count() as u32;
+            // Sessions are oldest first, the newest carries the current name
+            let latest = &sessions[sessions.len() - 1];
+            InstanceSessionStats {
+                instance_id: latest.instance_id.clone(),
+                instance_name: latest.instance_name.clone(),
+                sessions: sessions.len() as u32,
+                playtime_seconds: playtime,
+                average_session_seconds: playtime / sessions.len() as u64,
+                longest_session_seconds: sessions.iter().map(|s| s.duration_seconds).max().unwrap_or(0),
+                crashes,
+                crash_rate: crash_rate(crashes, known),
+                last_played: Some(latest.end_time),
+            }
+        })
+        .collect();
+    stats.sort_by(|a, b| b.playtime_seconds.cmp(&a.playtime_seconds));
+    stats
+}
+
+/// Crash rate of one instance on one game and loader version
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VersionCrashStats {
+    pub instance_id: String,
+    pub instance_name: String,
+    pub version_id: String,
+    pub mod_loader: ModLoader,
+    pub loader_version: Option<String>,
+    /// Mods installed in the most recent session on this version
+    pub mod_count: u32,
+    pub sessions: u32,
+    pub crashes: u32,
+    pub crash_rate: f32,
+}
+
+/// Crash rates grouped by instance, game version and loader version, worst first
+pub fn crash_stats() -> Vec<VersionCrashStats> {
+    let mut groups: BTreeMap<(String, String, Option<String>), Vec<PlaySession>> = BTreeMap::new();
+    for session in load_history() {
+        let key = (session.instance_id.clone(), session.version_id.clone(), session.loader_version.clone());
+        groups.entry(key).or_default().push(session);
+    }
+
+    let mut stats: Vec<VersionCrashStats> = groups.into_values()
+        .map(|sessions| {
+            let crashes = sessions.iter().filter(|s| s.crashed).count() as u32;
+            let known = sessions.iter().filter(|s| s.has_outcome()).count() as u32;
+            let latest = &sessions[sessions.len() - 1];
+            VersionCrashStats {
+                instance_id: latest.instance_id.clone(),
+                instance_name: latest.instance_name.clone(),
+                version_id: latest.version_id.clone(),
+                mod_loader: latest.mod_loader.clone(),
+                loader_version: latest.loader_version.clone(),
+                mod_count: latest.mod_count,
+                sessions: sessions.len() as u32,
+                crashes,
+                crash_rate: crash_rate(crashes, known),
+            }
+        })
+        .collect();
+    stats.sort_by(|a, b| b.crash_rate.total_cmp(&a.crash_rate).then(b.crashes.cmp(&a.crashes)));
+    stats
+}
+
+/// Consecutive days with play
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StreakStats {
+    /// Ends today, or yesterday if nothing was played yet today
+    pub current_streak_days: u32,
+    pub longest_streak_days: u32,
+    pub longest_streak_start: Option<String>,
+    pub days_played: u32,
+}
+
+pub fn streaks(instance_id: Option<&str>) -> StreakStats {
+    let days: BTreeSet<NaiveDate> = seconds_by_day(&sessions_for(instance_id))
+        .into_iter()
+        .filter(|(_, seconds)| *seconds > 0)
+        .map(|(day, _)| day)
+        .collect();
+
+    let mut stats = StreakStats {
+        days_played: days.len() as u32,
+        ..Default::default()
+    };
+
+    let mut run_start: Option<NaiveDate> = None;
+    let mut run_length = 0u32;
+    let mut previous: Option<NaiveDate> = None;
+    for day in &days {
+        if previous.and_then(|p| p.succ_opt()) == Some(*day) {
+            run_length += 1;
+        } else {
+            run_start = Some(*day);
+            run_length = 1;
+        }
+        if run_length > stats.longest_streak_days {
+            stats.longest_streak_days = run_length;
+            stats.longest_streak_start = run_start.map(|d| d.to_string());
+        }
+        previous = Some(*day);
+    }
+
+    let today = Local::now().date_naive();
+    let mut day = if days.contains(&today) { today } else { today - Duration::days(1) };
+    while days.contains(&day) {
+        stats.current_streak_days += 1;
+        day -= Duration::days(1);
+    }
+    stats
+}
+
+/// Totals over the whole history, for the global stats
+#[derive(Debug, Clone, Default)]
+pub struct HistorySummary {
+    pub total_sessions: u32,
+    pub average_session_seconds: u64,
+    pub longest_session_seconds: u64,
+    pub crashes: u32,
+}
+
+pub fn summary() -> HistorySummary {
+    let sessions = load_history();
+    if sessions.is_empty() {
+        return HistorySummary::default();
+    }
+    let total: u64 = sessions.iter().map(|s| s.duration_seconds).sum();
+    HistorySummary {
+        total_sessions: sessions.len() as u32,
+        average_session_seconds: total / sessions.len() as u64,
+        longest_session_seconds: sessions.iter().map(|s| s.duration_seconds).max().unwrap_or(0),
+        crashes: sessions.iter().filter(|s| s.crashed).count() as u32,
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn local_time(timestamp: u64) -> String {
+    Local.timestamp_opt(timestamp as i64, 0)
+        .earliest()
+        .map(|d| d.to_rfc3339())
+        .unwrap_or_default()
+}
+
+fn history_csv(sessions: &[PlaySession]) -> String {
+    let mut csv = String::from("id,instance_id,instance_name,start,end,duration_seconds,exit_code,crashed,killed,recovered,account,version_id,mod_loader,loader_version,mod_count\n");
+    for s in sessions {
+        let fields = [
+            s.id.clone(),
+            s.instance_id.clone(),
+            s.instance_name.clone(),
+            local_time(s.start_time),
+            local_time(s.end_time),
+            s.duration_seconds.to_string(),
+            s.exit_code.map(|c| c.to_string()).unwrap_or_default(),
+            s.crashed.to_string(),
+            s.killed.to_string(),
+            s.recovered.to_string(),
+            s.account.clone().unwrap_or_default(),
+            s.version_id.clone(),
+            s.mod_loader.to_string(),
+            s.loader_version.clone().unwrap_or_default(),
+            s.mod_count.to_string(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Write the history, oldest first, as "csv" or "json". Returns the number of sessions written.
+pub fn export_history(format: &str, destination: &Path, instance_id: Option<&str>) -> Result<usize, String> {
+    let sessions = sessions_for(instance_id);
+    let content = match format {
+        "csv" => history_csv(&sessions),
+        "json" => serde_json::to_string_pretty(&sessions)
+            .map_err(|e| format!("Failed to serialize session history: {}", e))?,
+        other => return Err(format!("Unknown export format: {}", other)),
+    };
+    fs::write(destination, content)
+        .map_err(|e| format!("Failed to write {}: {}", destination.display(), e))?;
+    Ok(sessions.len())
+}
@@ -0,0 +1,231 @@
+use crate::minecraft::instances::Instance;
+use crate::minecraft::settings;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+/// Wrapper, environment and hook commands for one launch, instance values over global defaults
+#[derive(Debug, Clone, Default)]
+pub struct LaunchHooks {
+    pub wrapper_command: Option<String>,
+    pub env_vars: Vec<(String, String)>,
+    pub pre_launch_command: Option<String>,
+    pub post_exit_command: Option<String>,
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.as_ref().map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+pub fn resolve_hooks(instance: &Instance) -> LaunchHooks {
+    let global = settings::load_settings();
+
+    // Global variables first so the instance can override them
+    let mut env: HashMap<String, String> = global.env_vars.clone().unwrap_or_default();
+    env.extend(instance.env_vars.clone().unwrap_or_default());
+    let mut env_vars: Vec<(String, String)> = env.into_iter()
+        .filter(|(k, _)| !k.trim().is_empty())
+        .collect();
+    env_vars.sort();
+
+    LaunchHooks {
+        wrapper_command: non_empty(&instance.wrapper_command).or_else(|| non_empty(&global.wrapper_command)),
+        env_vars,
+        pre_launch_command: non_empty(&instance.pre_launch_command).or_else(|| non_empty(&global.pre_launch_command)),
+        post_exit_command: non_empty(&instance.post_exit_command).or_else(|| non_empty(&global.post_exit_command)),
+    }
+}
+
+/// Values for the $INST_* placeholders, also exported to hooks and the game as environment variables
+pub fn placeholder_values(instance: &Instance, java_path: &Path) -> Vec<(&'static str, String)> {
+    vec![
+        ("INST_ID", instance.id.clone()),
+        ("INST_NAME", instance.name.clone()),
+        ("INST_DIR", instance.get_directory().to_string_lossy().to_string()),
+        ("INST_MC_DIR", instance.get_game_directory().to_string_lossy().to_string()),
+        ("INST_JAVA", java_path.to_string_lossy().to_string()),
+    ]
+}
+
+/// Replace $INST_* and ${INST_*} placeholders
+pub fn expand_placeholders(value: &str, placeholders: &[(&'static str, String)]) -> String {
+    let mut result = value.to_string();
+    // Longest names first so $INST_MC_DIR isn't eaten by a shorter name
+    let mut sorted: Vec<&(&str, String)> = placeholders.iter().collect();
+    sorted.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+    for (name, replacement) in sorted {
+        result = result.replace(&format!("${{{}}}", name), replacement);
+        result = result.replace(&format!("${}", name), replacement);
+    }
+    result
+}
+
+/// Split a command line into words, honouring single and double quotes
+pub fn split_command(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    words
+}
+
+/// Run a hook through the system shell in the game directory. The command isn't expanded
+/// here, the shell reads $INST_* (%INST_*% on Windows) from the environment so names and
+/// paths never become part of the command text. Fails with the hook's output if it exits
+/// with a non-zero status.
+pub fn run_hook(label: &str, command: &str, instance: &Instance, java_path: &Path, env_vars: &[(String, String)]) -> Result<(), String> {
+    let placeholders = placeholder_values(instance, java_path);
+
+    let mut process = if cfg!(target_os = "windows") {
+        let mut c = Command::new("cmd");
+        c.arg("/C").arg(command);
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.arg("-c").arg(command);
+        c
+    };
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        process.creation_flags(0x08000000);
+    }
+
+    let game_dir = instance.get_game_directory();
+    if game_dir.exists() {
+        process.current_dir(&game_dir);
+    }
+    process.envs(placeholders.iter().map(|(k, v)| (*k, v.as_str())));
+    process.envs(env_vars.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+
+    log::info!("Running {} hook: {}", label, command);
+    let output = process.output()
+        .map_err(|e| format!("Failed to run {} hook: {}", label, e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        let mut message = format!("The {} hook failed ({})", label, output.status);
+        for text in [stdout.trim(), stderr.trim()] {
+            if !text.is_empty() {
+                message.push_str(&format!("\n{}", text));
+            }
+        }
+        return Err(message);
+    }
+
+    if !stdout.trim().is_empty() {
+        log::info!("Output of the {} hook: {}", label, stdout.trim());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn split_plain_words() {
+        assert_eq!(split_command("  gamemoderun   --flag\tvalue \n"), words(&["gamemoderun", "--flag", "value"]));
+        assert!(split_command("").is_empty());
+        assert!(split_command("   ").is_empty());
+    }
+
+    #[test]
+    fn split_quotes() {
+        assert_eq!(split_command(r#""/opt/my tools/wrap.sh" -x"#), words(&["/opt/my tools/wrap.sh", "-x"]));
+        assert_eq!(split_command("'single quoted' word"), words(&["single quoted", "word"]));
+        // Quotes of the other kind are kept, and quoted parts join the surrounding word
+        assert_eq!(split_command(r#"'say "hi"' "it's""#), words(&[r#"say "hi""#, "it's"]));
+        assert_eq!(split_command(r#"-Dname="a b"c"#), words(&["-Dname=a bc"]));
+    }
+
+    #[test]
+    fn split_escapes() {
+        // Backslashes escape inside double quotes only
+        assert_eq!(split_command(r#""a \"quoted\" \\ word""#), words(&[r#"a "quoted" \ word"#]));
+        assert_eq!(split_command(r#"'a \n b'"#), words(&[r#"a \n b"#]));
+        assert_eq!(split_command(r#"C:\Java\bin\java.exe"#), words(&[r#"C:\Java\bin\java.exe"#]));
+    }
+
+    #[test]
+    fn split_empty_quoted_words() {
+        assert_eq!(split_command(r#"a "" b"#), words(&["a", "", "b"]));
+        assert_eq!(split_command("''"), words(&[""]));
+    }
+
+    fn placeholders() -> Vec<(&'static str, String)> {
+        vec![
+            ("INST_ID", "abc".to_string()),
+            ("INST_DIR", "/home/me/Application Support/abc".to_string()),
+            ("INST_MC_DIR", "/home/me/Application Support/abc/.minecraft".to_string()),
+        ]
+    }
+
+    #[test]
+    fn expand_both_forms() {
+        let placeholders = placeholders();
+        assert_eq!(expand_placeholders("$INST_ID-${INST_ID}", &placeholders), "abc-abc");
+        assert_eq!(expand_placeholders("${INST_DIR}/wrap.sh", &placeholders), "/home/me/Application Support/abc/wrap.sh");
+    }
+
+    #[test]
+    fn expand_prefers_longest_name() {
+        let placeholders = placeholders();
+        assert_eq!(expand_placeholders("$INST_MC_DIR/mods", &placeholders), "/home/me/Application Support/abc/.minecraft/mods");
+    }
+
+    #[test]
+    fn expand_leaves_unknown_names() {
+        let placeholders = placeholders();
+        assert_eq!(expand_placeholders("$HOME $INST_JAVA plain", &placeholders), "$HOME $INST_JAVA plain");
+    }
+
+    #[test]
+    fn split_before_expanding_keeps_paths_whole() {
+        let placeholders = placeholders();
+        let wrapper: Vec<String> = split_command("$INST_DIR/wrap.sh --log \"$INST_MC_DIR/logs\"").iter()
+            .map(|word| expand_placeholders(word, &placeholders))
+            .collect();
+        assert_eq!(wrapper, words(&[
+            "/home/me/Application Support/abc/wrap.sh",
+            "--log",
+            "/home/me/Application Support/abc/.minecraft/logs",
+        ]));
+    }
+}
@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use uuid::Uuid;
 
 use crate::minecraft::downloader::{get_instances_dir, get_minecraft_dir};
+use crate::minecraft::jvm::JvmProfile;
 
 // ----------
 // Windows console hiding
@@ -21,6 +23,7 @@ pub enum ModLoader {
     Fabric,
     Forge,
     NeoForge,
+    Quilt,
 }
 
 impl std::fmt::Display for ModLoader {
@@ -30,6 +33,7 @@ impl std::fmt::Display for ModLoader {
             ModLoader::Fabric => write!(f, "Fabric"),
             ModLoader::Forge => write!(f, "Forge"),
             ModLoader::NeoForge => write!(f, "NeoForge"),
+            ModLoader::Quilt => write!(f, "Quilt"),
         }
     }
 }
@@ -68,6 +72,36 @@ pub struct Instance {
     pub total_launches: u64,
     #[serde(default)]
     pub color_accent: Option<String>,
+    /// Back up every world played during a session once the game exits
+    #[serde(default)]
+    pub backup_worlds_on_exit: bool,
+    /// Retention for automatic world backups: "keep_last" or "keep_daily"
+    #[serde(default)]
+    pub backup_retention: Option<String>,
+    /// Backups (keep_last) or days (keep_daily) to keep
+    #[serde(default)]
+    pub backup_retention_count: Option<u32>,
+    /// Command the Java process is started through, e.g. "gamemoderun" or "prime-run"
+    #[serde(default)]
+    pub wrapper_command: Option<String>,
+    /// Extra environment variables for the game process
+    #[serde(default)]
+    pub env_vars: Option<HashMap<String, String>>,
+    /// Run before launching, a non-zero exit aborts the launch
+    #[serde(default)]
+    pub pre_launch_command: Option<String>,
+    /// Run after the game exits
+    #[serde(default)]
+    pub post_exit_command: Option<String>,
+    /// Managed or detected Java runtime pinned by ID, takes precedence over java_path
+    #[serde(default)]
+    pub java_runtime: Option<String>,
+    /// GC and tuning flag set, None keeps G1 unless jvm_args pick a collector
+    #[serde(default)]
+    pub jvm_profile: Option<JvmProfile>,
+    /// Size the heap from system RAM and the installed mods instead of memory_min/memory_max
+    #[serde(default)]
+    pub auto_memory: bool,
 }
 
 impl Instance {
@@ -92,6 +126,16 @@ impl Instance {
             playtime_seconds: 0,
             total_launches: 0,
             color_accent: None,
+            backup_worlds_on_exit: false,
+            backup_retention: None,
+            backup_retention_count: None,
+            wrapper_command: None,
+            env_vars: None,
+            pre_launch_command: None,
+            post_exit_command: None,
+            java_runtime: None,
+            jvm_profile: None,
+            auto_memory: false,
         }
     }
     
@@ -260,6 +304,16 @@ pub fn clone_instance(instance_id: &str, new_name: String) -> Result<Instance, S
         playtime_seconds: 0, // Reset playtime for clone
         total_launches: 0,
         color_accent: source.color_accent.clone(),
+        backup_worlds_on_exit: source.backup_worlds_on_exit,
+        backup_retention: source.backup_retention.clone(),
+        backup_retention_count: source.backup_retention_count,
+        wrapper_command: source.wrapper_command.clone(),
+        env_vars: source.env_vars.clone(),
+        pre_launch_command: source.pre_launch_command.clone(),
+        post_exit_command: source.post_exit_command.clone(),
+        java_runtime: source.java_runtime.clone(),
+        jvm_profile: source.jvm_profile,
+        auto_memory: source.auto_memory,
     };
     
     // Create new instance directory
@@ -323,34 +377,124 @@ fn copy_dir_recursive(src: &PathBuf, dst: &PathBuf) -> std::io::Result<()> {
 
 // ============== SESSION TRACKING ==============
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GameSession {
     pub instance_id: String,
     pub start_time: u64,
     pub pid: Option<u32>,
+    /// Game directory the process runs in, used to tell it apart from a reused PID
+    #[serde(default)]
+    pub game_dir: Option<String>,
 }
 
-fn get_session_file_path() -> PathBuf {
+/// Session file from before sessions were tracked per instance
+fn get_legacy_session_file_path() -> PathBuf {
     get_minecraft_dir().join("active_session.json")
 }
 
+/// One record per running instance, sessions/<instance_id>.json
+fn get_sessions_dir() -> PathBuf {
+    get_minecraft_dir().join("sessions")
+}
+
+fn get_session_file_path(instance_id: &str) -> PathBuf {
+    get_sessions_dir().join(format!("{}.json", instance_id))
+}
+
 /// Write an active session to disk (called when game launches)
-pub fn write_active_session(instance_id: &str, start_time: u64, pid: Option<u32>) -> Result<(), String> {
+pub fn write_active_session(instance: &Instance, start_time: u64, pid: Option<u32>) -> Result<(), String> {
     let session = GameSession {
-        instance_id: instance_id.to_string(),
+        instance_id: instance.id.clone(),
         start_time,
         pid,
+        game_dir: Some(instance.get_game_directory().to_string_lossy().to_string()),
     };
     let content = serde_json::to_string(&session)
         .map_err(|e| format!("Failed to serialize session: {}", e))?;
-    fs::write(get_session_file_path(), content)
+    fs::create_dir_all(get_sessions_dir())
+        .map_err(|e| format!("Failed to create sessions directory: {}", e))?;
+    fs::write(get_session_file_path(&instance.id), content)
         .map_err(|e| format!("Failed to write session file: {}", e))?;
     Ok(())
 }
 
-/// Clear the active session file (called when game exits normally)
-pub fn clear_active_session() {
-    let _ = fs::remove_file(get_session_file_path());
+/// Clear an instance's session file (called when its game exits normally)
+pub fn clear_active_session(instance_id: &str) {
+    let _ = fs::remove_file(get_session_file_path(instance_id));
+}
+
+/// Clear every session file (called when the launcher kills all games on exit)
+pub fn clear_all_sessions() {
+    let _ = fs::remove_dir_all(get_sessions_dir());
+    let _ = fs::remove_file(get_legacy_session_file_path());
+}
+
+/// Sessions left on disk, including the old single-session file
+fn read_active_sessions() -> Vec<(PathBuf, GameSession)> {
+    let mut paths = vec![get_legacy_session_file_path()];
+    if let Ok(entries) = fs::read_dir(get_sessions_dir()) {
+        paths.extend(entries.flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().map_or(false, |ext| ext == "json")));
+    }
+
+    paths.into_iter()
+        .filter_map(|path| {
+            let content = fs::read_to_string(&path).ok()?;
+            match serde_json::from_str::<GameSession>(&content) {
+                Ok(session) => Some((path, session)),
+                Err(_) => {
+                    let _ = fs::remove_file(&path);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Full command line of a running process
+fn process_command_line(pid: u32) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let raw = fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+        Some(String::from_utf8_lossy(&raw).replace('\0', " "))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::process::Command;
+        let out = Command::new("powershell")
+            .args(&["-NoProfile", "-Command", &format!("(Get-CimInstance Win32_Process -Filter \"ProcessId={}\").CommandLine", pid)])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()?;
+        Some(String::from_utf8_lossy(&out.stdout).trim().to_string())
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        let out = std::process::Command::new("ps")
+            .args(&["-p", &pid.to_string(), "-o", "command="])
+            .output()
+            .ok()?;
+        Some(String::from_utf8_lossy(&out.stdout).trim().to_string())
+    }
+}
+
+/// Whether a PID still belongs to this session's game: a Java process started for the
+/// instance's game directory, not an unrelated process that got the PID after a reboot
+fn is_session_process(pid: u32, session: &GameSession) -> bool {
+    if !is_process_running(pid) {
+        return false;
+    }
+    let Some(command_line) = process_command_line(pid) else { return false };
+    let is_java = command_line.to_lowercase().contains("java");
+    let in_game_dir = match &session.game_dir {
+        Some(game_dir) => command_line.contains(game_dir.as_str()),
+        // Old session files don't know the directory, fall back to the instance's current one
+        None => get_instance(&session.instance_id)
+            .map(|i| command_line.contains(&*i.get_game_directory().to_string_lossy()))
+            .unwrap_or(false),
+    };
+    is_java && in_game_dir
 }
 
 pub fn is_process_running(pid: u32) -> bool {
@@ -376,54 +520,67 @@ pub fn is_process_running(pid: u32) -> bool {
     }
 }
 
-/// Check for orphaned session on startup and credit playtime or recover process
-pub fn recover_orphaned_session() -> Option<(String, u64, Option<u32>, u64)> {
-    let session_path = get_session_file_path();
-    if !session_path.exists() {
-        return None;
-    }
-    
-    let content = fs::read_to_string(&session_path).ok()?;
-    let session: GameSession = serde_json::from_str(&content).ok()?;
-    
-    // Read current instance data for original playtime
-    let original_playtime = if let Ok(inst) = get_instance(&session.instance_id) {
-        inst.playtime_seconds
-    } else {
-        0
-    };
-
-    // Check if the process is still running
-    if let Some(pid) = session.pid {
-        if is_process_running(pid) {
-            // Game is still running! Return info to re-track it
-            return Some((session.instance_id, session.start_time, Some(pid), original_playtime));
-        }
-    }
+/// A session found on startup, either still running or already credited
+#[derive(Debug, Clone)]
+pub struct RecoveredSession {
+    pub instance_id: String,
+    pub start_time: u64,
+    /// Set when the game is still running and should be tracked again
+    pub pid: Option<u32>,
+    /// Playtime credited for a session whose game is gone
+    pub credited_seconds: u64,
+}
 
-    // Process is not running, calculate time since session started (assume game ran until now)
+/// Check for orphaned sessions on startup, crediting the playtime of games that are gone and
+/// returning the ones still running so they can be re-attached
+pub fn recover_orphaned_sessions() -> Vec<RecoveredSession> {
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs();
-    
-    // Only credit if the session is less than 24 hours old (sanity check)
-    let duration = now.saturating_sub(session.start_time);
-    if duration > 86400 {
-        // Session is stale, just clear it
-        clear_active_session();
-        return None;
-    }
-    
-    // Credit the playtime
-    if let Ok(mut instance) = get_instance(&session.instance_id) {
-        instance.playtime_seconds += duration;
-        let _ = update_instance(instance);
+    let mut recovered = Vec::new();
+
+    for (path, session) in read_active_sessions() {
+        // Check if the process is still running
+        if let Some(pid) = session.pid {
+            if is_session_process(pid, &session) {
+                // Move old session files to the per-instance layout
+                if path == get_legacy_session_file_path() {
+                    if let Ok(instance) = get_instance(&session.instance_id) {
+                        let _ = write_active_session(&instance, session.start_time, Some(pid));
+                    }
+                    let _ = fs::remove_file(&path);
+                }
+                recovered.push(RecoveredSession {
+                    instance_id: session.instance_id,
+                    start_time: session.start_time,
+                    pid: Some(pid),
+                    credited_seconds: 0,
+                });
+                continue;
+            }
+        }
+
+        let _ = fs::remove_file(&path);
+
+        // Process is not running, assume the game ran until now. Sessions older
+        // than 24 hours are stale and only cleared.
+        let duration = now.saturating_sub(session.start_time);
+        if duration > 86400 {
+            continue;
+        }
+
+        if let Ok(mut instance) = get_instance(&session.instance_id) {
+            instance.playtime_seconds += duration;
+            let _ = update_instance(instance);
+        }
+        recovered.push(RecoveredSession {
+            instance_id: session.instance_id,
+            start_time: session.start_time,
+            pid: None,
+            credited_seconds: duration,
+        });
     }
-    
-    // Clear the session file
-    clear_active_session();
-    
-    // Return with None PID to indicate it's already accounted for
-    Some((session.instance_id, duration, None, original_playtime))
+
+    recovered
 }
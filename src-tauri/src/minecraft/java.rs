@@ -1,12 +1,683 @@
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
 
-use crate::minecraft::downloader::get_minecraft_dir;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+use tauri::{AppHandle, Emitter};
+
+use crate::minecraft::downloader::{download_file, get_minecraft_dir, save_verification_cache, DownloadProgress};
+use crate::minecraft::instances;
+use crate::minecraft::versions;
+
+/// Index of Mojang's java-runtime-* components for every platform
+const JAVA_RUNTIMES_MANIFEST: &str = "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
+const ADOPTIUM_API: &str = "https://api.adoptium.net/v3";
+/// Copy of the component manifest kept next to a Mojang runtime for validation
+const RUNTIME_MANIFEST_FILE: &str = ".manifest.json";
+const CONCURRENT_DOWNLOADS: usize = 16;
+
+/// A Java installation, either managed by the launcher under java/ or found on the system
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JavaRuntime {
+    /// Folder name for managed runtimes, "system-<hash>" for detected ones
+    pub id: String,
+    pub path: String,
+    pub version: Option<String>,
+    pub major_version: Option<u32>,
+    pub vendor: Option<String>,
+    pub arch: Option<String>,
+    pub managed: bool,
+    /// "mojang", "temurin" or "system"
+    pub source: String,
+}
+
+/// Result of checking a runtime's files and that its java binary starts
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JavaValidation {
+    pub runtime_id: String,
+    pub valid: bool,
+    pub problems: Vec<String>,
+    pub runtime: Option<JavaRuntime>,
+}
+
+/// What a java binary reports about itself
+#[derive(Debug, Clone)]
+pub struct JavaProbe {
+    pub version: String,
+    pub major: u32,
+    pub vendor: Option<String>,
+    pub arch: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuntimeEntry {
+    manifest: RuntimeDownload,
+    version: RuntimeVersion,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuntimeVersion {
+    name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct RuntimeDownload {
+    sha1: String,
+    size: u64,
+    url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RuntimeManifest {
+    files: HashMap<String, RuntimeFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RuntimeFile {
+    #[serde(rename = "type")]
+    file_type: String,
+    #[serde(default)]
+    executable: bool,
+    #[serde(default)]
+    downloads: Option<RuntimeFileDownloads>,
+    #[serde(default)]
+    target: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RuntimeFileDownloads {
+    raw: RuntimeDownload,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdoptiumAsset {
+    binary: AdoptiumBinary,
+    release_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdoptiumBinary {
+    package: AdoptiumPackage,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdoptiumPackage {
+    /// SHA-256 of the archive
+    checksum: String,
+    link: String,
+    name: String,
+    size: u64,
+}
+
+/// Folder holding every runtime the launcher installed
+pub fn get_java_dir() -> PathBuf {
+    get_minecraft_dir().join("java")
+}
+
+fn java_binary_name() -> &'static str {
+    if cfg!(target_os = "windows") { "java.exe" } else { "java" }
+}
+
+fn http_client() -> Result<reqwest::Client, reqwest::Error> {
+    reqwest::Client::builder()
+        .user_agent(format!("PaletheaLauncher/{}", super::get_launcher_version()))
+        .build()
+}
+
+fn emit_progress(app_handle: Option<&AppHandle>, stage: String, current: u32, total: u32, downloaded_bytes: u64, total_bytes: u64) {
+    let Some(handle) = app_handle else { return };
+    let percentage = if total_bytes > 0 {
+        downloaded_bytes as f32 * 100.0 / total_bytes as f32
+    } else if total > 0 {
+        current as f32 * 100.0 / total as f32
+    } else {
+        0.0
+    };
+    let _ = handle.emit("java-download-progress", DownloadProgress {
+        stage,
+        current,
+        total,
+        percentage,
+        total_bytes: Some(total_bytes),
+        downloaded_bytes: Some(downloaded_bytes),
+    });
+}
+
+/// Major version of a Java version string, "1.8.0_392" is 8 and "17.0.8" is 17
+pub fn parse_major(version: &str) -> Option<u32> {
+    let mut parts = version.split(|c: char| !c.is_ascii_digit()).filter(|p| !p.is_empty());
+    let first: u32 = parts.next()?.parse().ok()?;
+    if first == 1 {
+        return parts.next()?.parse().ok();
+    }
+    Some(first)
+}
+
+/// Same naming as the version JSON os.arch rules
+fn normalize_arch(arch: &str) -> String {
+    match arch {
+        "amd64" | "x86_64" | "x64" => "x86_64",
+        "x86" | "i386" | "i486" | "i586" | "i686" => "x86",
+        "aarch64" | "arm64" => "arm64",
+        "arm" | "aarch32" => "arm32",
+        other => other,
+    }.to_string()
+}
+
+/// Probe results by canonical binary path, reused while the binary's mtime is unchanged
+static PROBE_CACHE: LazyLock<Mutex<HashMap<PathBuf, (Option<SystemTime>, Option<JavaProbe>)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Version, vendor and architecture of a java binary. Each binary is only started once
+/// until it changes on disk, so listing runtimes stays cheap on the launch path.
+pub fn probe_java(java_path: &Path) -> Option<JavaProbe> {
+    let canonical = fs::canonicalize(java_path).unwrap_or_else(|_| java_path.to_path_buf());
+    let modified = fs::metadata(&canonical).and_then(|m| m.modified()).ok();
+
+    if let Ok(cache) = PROBE_CACHE.lock() {
+        if let Some((cached_modified, probe)) = cache.get(&canonical) {
+            if *cached_modified == modified {
+                return probe.clone();
+            }
+        }
+    }
+
+    let probe = run_probe(java_path);
+    if let Ok(mut cache) = PROBE_CACHE.lock() {
+        cache.insert(canonical, (modified, probe.clone()));
+    }
+    probe
+}
+
+/// Run the binary and read its version, vendor and architecture from the system properties
+fn run_probe(java_path: &Path) -> Option<JavaProbe> {
+    let mut command = Command::new(java_path);
+    command.arg("-XshowSettings:properties").arg("-version");
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x08000000);
+    }
+
+    let output = command.output().ok()?;
+    let mut combined = String::from_utf8_lossy(&output.stderr).to_string();
+    combined.push_str(&String::from_utf8_lossy(&output.stdout));
+
+    let mut properties: HashMap<&str, &str> = HashMap::new();
+    for line in combined.lines() {
+        if let Some((key, value)) = line.trim().split_once(" = ") {
+            properties.entry(key.trim()).or_insert(value.trim());
+        }
+    }
+
+    // Fall back to the `java version "x"` banner if the properties are missing
+    let version = match properties.get("java.version") {
+        Some(v) => v.to_string(),
+        None => {
+            let line = combined.lines().find(|l| l.contains("version \""))?;
+            let start = line.find('"')? + 1;
+            let end = line[start..].find('"')? + start;
+            line[start..end].to_string()
+        }
+    };
+
+    Some(JavaProbe {
+        major: parse_major(&version)?,
+        version,
+        vendor: properties.get("java.vendor").map(|v| v.to_string()),
+        arch: properties.get("os.arch").map(|a| normalize_arch(a)),
+    })
+}
+
+/// Major version of a java binary, None if it doesn't run
+pub fn get_java_major(java_path: &Path) -> Option<u32> {
+    probe_java(java_path).map(|probe| probe.major)
+}
+
+/// Locate bin/java inside an installation, also looking one folder down and in macOS bundles
+pub fn find_java_binary(install_dir: &Path) -> Option<PathBuf> {
+    let java_name = java_binary_name();
+    let layouts = |dir: &Path| [
+        dir.join("bin").join(java_name),
+        dir.join("Contents").join("Home").join("bin").join(java_name),
+    ];
+
+    if let Some(found) = layouts(install_dir).into_iter().find(|p| p.exists()) {
+        return Some(found);
+    }
+
+    if let Ok(entries) = fs::read_dir(install_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                if let Some(found) = layouts(&path).into_iter().find(|p| p.exists()) {
+                    return Some(found);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn build_runtime(id: String, java_path: &Path, managed: bool, source: &str) -> JavaRuntime {
+    let probe = probe_java(java_path);
+    JavaRuntime {
+        id,
+        path: java_path.to_string_lossy().to_string(),
+        version: probe.as_ref().map(|p| p.version.clone()),
+        major_version: probe.as_ref().map(|p| p.major),
+        vendor: probe.as_ref().and_then(|p| p.vendor.clone()),
+        arch: probe.and_then(|p| p.arch),
+        managed,
+        source: source.to_string(),
+    }
+}
+
+/// Managed runtime IDs are plain folder names under java/
+fn is_valid_managed_id(id: &str) -> bool {
+    !id.is_empty() && !id.starts_with('.') && Path::new(id).components().count() == 1
+        && matches!(Path::new(id).components().next(), Some(Component::Normal(_)))
+}
+
+fn managed_source(id: &str) -> &'static str {
+    if id.starts_with("temurin-") { "temurin" } else { "mojang" }
+}
+
+/// A runtime installed under java/, probed even when broken so it can still be validated or removed
+pub fn get_managed_runtime(id: &str) -> Option<JavaRuntime> {
+    if !is_valid_managed_id(id) {
+        return None;
+    }
+    let install_dir = get_java_dir().join(id);
+    if !install_dir.is_dir() {
+        return None;
+    }
+    let java_path = find_java_binary(&install_dir).unwrap_or_else(|| install_dir.join("bin").join(java_binary_name()));
+    Some(build_runtime(id.to_string(), &java_path, true, managed_source(id)))
+}
+
+fn list_managed_runtimes() -> Vec<JavaRuntime> {
+    let Ok(entries) = fs::read_dir(get_java_dir()) else { return Vec::new() };
+    let mut ids: Vec<String> = entries.flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    ids.sort();
+    ids.iter().filter_map(|id| get_managed_runtime(id)).collect()
+}
+
+/// Java binaries from JAVA_HOME, PATH and the usual install locations
+fn system_java_candidates() -> Vec<PathBuf> {
+    let java_name = java_binary_name();
+    let mut candidates = Vec::new();
+
+    if let Ok(java_home) = std::env::var("JAVA_HOME") {
+        candidates.push(PathBuf::from(java_home).join("bin").join(java_name));
+    }
+
+    if let Some(path_var) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&path_var) {
+            candidates.push(dir.join(java_name));
+        }
+    }
+
+    let mut common_paths: Vec<PathBuf> = if cfg!(target_os = "windows") {
+        vec![
+            "C:\\Program Files\\Java",
+            "C:\\Program Files (x86)\\Java",
+            "C:\\Program Files\\Eclipse Adoptium",
+            "C:\\Program Files\\Microsoft",
+            "C:\\Program Files\\Zulu",
+            "C:\\Program Files\\BellSoft",
+        ]
+    } else if cfg!(target_os = "macos") {
+        vec![
+            "/Library/Java/JavaVirtualMachines",
+            "/usr/local/opt/openjdk",
+            "/opt/homebrew/opt/openjdk",
+        ]
+    } else {
+        vec![
+            "/usr/lib/jvm",
+            "/usr/lib64/jvm",
+            "/usr/java",
+            "/opt/java",
+        ]
+    }.into_iter().map(PathBuf::from).collect();
+
+    if let Some(home) = dirs::home_dir() {
+        common_paths.push(home.join(".jdks"));
+        common_paths.push(home.join(".sdkman").join("candidates").join("java"));
+    }
+
+    for base in common_paths {
+        // Some locations are a JDK themselves, others hold one per folder
+        if let Some(found) = find_java_binary(&base) {
+            candidates.push(found);
+        }
+        if let Ok(entries) = fs::read_dir(&base) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                for java_path in [
+                    path.join("bin").join(java_name),
+                    path.join("Contents").join("Home").join("bin").join(java_name),
+                ] {
+                    candidates.push(java_path);
+                }
+            }
+        }
+    }
+
+    candidates.into_iter().filter(|p| p.is_file()).collect()
+}
+
+fn system_runtime_id(canonical: &Path) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(canonical.to_string_lossy().as_bytes());
+    let hash = format!("{:x}", hasher.finalize());
+    format!("system-{}", &hash[..12])
+}
+
+/// Every runtime the launcher knows about, managed ones first.
+/// System installs are deduplicated by their resolved path and skipped if they don't run.
+pub fn list_runtimes() -> Vec<JavaRuntime> {
+    let mut runtimes = list_managed_runtimes();
+    let java_dir = fs::canonicalize(get_java_dir()).unwrap_or_else(|_| get_java_dir());
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+
+    for candidate in system_java_candidates() {
+        let canonical = fs::canonicalize(&candidate).unwrap_or_else(|_| candidate.clone());
+        if canonical.starts_with(&java_dir) || !seen.insert(canonical.clone()) {
+            continue;
+        }
+        let runtime = build_runtime(system_runtime_id(&canonical), &candidate, false, "system");
+        if runtime.major_version.is_some() {
+            runtimes.push(runtime);
+        }
+    }
+    runtimes
+}
+
+/// Look up a runtime by ID
+pub fn find_runtime(id: &str) -> Option<JavaRuntime> {
+    if id.starts_with("system-") {
+        return list_runtimes().into_iter().find(|r| r.id == id);
+    }
+    get_managed_runtime(id)
+}
+
+/// Delete a managed runtime. Refuses while an instance still pins it.
+pub fn remove_runtime(id: &str) -> Result<(), String> {
+    if get_managed_runtime(id).is_none() {
+        return Err(format!("Java runtime {} is not managed by the launcher", id));
+    }
+
+    let pinned_by: Vec<String> = instances::load_instances()?
+        .into_iter()
+        .filter(|i| i.java_runtime.as_deref() == Some(id))
+        .map(|i| i.name)
+        .collect();
+    if !pinned_by.is_empty() {
+        return Err(format!("Java runtime {} is used by: {}", id, pinned_by.join(", ")));
+    }
+
+    fs::remove_dir_all(get_java_dir().join(id))
+        .map_err(|e| format!("Failed to remove Java runtime {}: {}", id, e))
+}
+
+fn sha1_of_file(path: &Path) -> Option<String> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut hasher = Sha1::new();
+    let mut buffer = [0u8; 65536];
+    loop {
+        let read = file.read(&mut buffer).ok()?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+/// Manifest paths are relative and must stay inside the runtime folder
+fn is_safe_relative(path: &str) -> bool {
+    !path.is_empty() && Path::new(path).components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Re-hash every file of a Mojang runtime against its manifest and check the binary starts
+pub fn validate_runtime(id: &str) -> JavaValidation {
+    let mut problems = Vec::new();
+    let Some(runtime) = find_runtime(id) else {
+        return JavaValidation {
+            runtime_id: id.to_string(),
+            valid: false,
+            problems: vec![format!("Java runtime {} was not found", id)],
+            runtime: None,
+        };
+    };
+
+    if runtime.managed && runtime.source == "mojang" {
+        let install_dir = get_java_dir().join(id);
+        let manifest = fs::read_to_string(install_dir.join(RUNTIME_MANIFEST_FILE)).ok()
+            .and_then(|c| serde_json::from_str::<RuntimeManifest>(&c).ok());
+        match manifest {
+            Some(manifest) => {
+                let mut names: Vec<&String> = manifest.files.keys().collect();
+                names.sort();
+                for name in names {
+                    let file = &manifest.files[name];
+                    let path = install_dir.join(name);
+                    match (file.file_type.as_str(), &file.downloads) {
+                        ("file", Some(downloads)) => match sha1_of_file(&path) {
+                            None => problems.push(format!("Missing file {}", name)),
+                            Some(hash) if !hash.eq_ignore_ascii_case(&downloads.raw.sha1) => {
+                                problems.push(format!("Checksum mismatch for {}", name));
+                            }
+                            _ => {}
+                        },
+                        ("directory", _) if !path.is_dir() => problems.push(format!("Missing directory {}", name)),
+                        ("link", _) if cfg!(unix) && fs::symlink_metadata(&path).is_err() => {
+                            problems.push(format!("Missing link {}", name));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            None => problems.push("The runtime manifest is missing, reinstall the runtime".to_string()),
+        }
+    }
+
+    // Start the binary for real, a cached probe says nothing about files changed since
+    if run_probe(Path::new(&runtime.path)).is_none() {
+        problems.push(format!("{} did not report a Java version", runtime.path));
+    }
+
+    JavaValidation {
+        runtime_id: id.to_string(),
+        valid: problems.is_empty(),
+        problems,
+        runtime: Some(runtime),
+    }
+}
+
+/// Platform key of the Mojang runtime index, None where Mojang ships no runtimes
+fn mojang_platform() -> Option<&'static str> {
+    match (versions::get_os_name(), versions::get_os_arch()) {
+        ("windows", "x86_64") => Some("windows-x64"),
+        ("windows", "x86") => Some("windows-x86"),
+        ("windows", "arm64") => Some("windows-arm64"),
+        ("osx", "arm64") => Some("mac-os-arm64"),
+        ("osx", _) => Some("mac-os"),
+        ("linux", "x86_64") => Some("linux"),
+        ("linux", "x86") => Some("linux-i386"),
+        _ => None,
+    }
+}
+
+/// Install or repair one of Mojang's runtime components (java-runtime-gamma, jre-legacy, ...).
+/// Every file is checked against the SHA1 in the component manifest, files that already
+/// match are kept. Returns the java binary.
+pub async fn install_mojang_runtime(component: &str, app_handle: Option<&AppHandle>) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+    if !is_valid_managed_id(component) || component.starts_with("temurin-") {
+        return Err(format!("Invalid Java runtime component: {}", component).into());
+    }
+    let platform = mojang_platform()
+        .ok_or_else(|| format!("Mojang doesn't provide Java runtimes for {}-{}", versions::get_os_name(), versions::get_os_arch()))?;
+
+    let client = http_client()?;
+    let index: HashMap<String, HashMap<String, Vec<RuntimeEntry>>> = client
+        .get(JAVA_RUNTIMES_MANIFEST)
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    let entry = index.get(platform)
+        .and_then(|components| components.get(component))
+        .and_then(|entries| entries.first())
+        .ok_or_else(|| format!("Mojang has no {} runtime for {}", component, platform))?;
+
+    let manifest_bytes = client.get(&entry.manifest.url).send().await?.bytes().await?;
+    let mut hasher = Sha1::new();
+    hasher.update(&manifest_bytes);
+    let manifest_hash = format!("{:x}", hasher.finalize());
+    if !manifest_hash.eq_ignore_ascii_case(&entry.manifest.sha1) {
+        return Err(format!("SHA1 verification failed for the {} manifest", component).into());
+    }
+    let manifest: RuntimeManifest = serde_json::from_slice(&manifest_bytes)?;
+
+    log::info!("Installing Java runtime {} {} for {}", component, entry.version.name, platform);
+    let install_dir = get_java_dir().join(component);
+    fs::create_dir_all(&install_dir)?;
+
+    struct RuntimeDownloadTask {
+        url: String,
+        path: PathBuf,
+        sha1: String,
+        size: u64,
+        executable: bool,
+    }
+
+    let mut downloads = Vec::new();
+    let mut links = Vec::new();
+    let mut total_bytes = 0u64;
+    for (name, file) in &manifest.files {
+        if !is_safe_relative(name) {
+            log::warn!("Skipping unsafe path {} in the {} manifest", name, component);
+            continue;
+        }
+        let path = install_dir.join(name);
+        match file.file_type.as_str() {
+            "directory" => fs::create_dir_all(&path)?,
+            "file" => {
+                let Some(downloads_info) = &file.downloads else { continue };
+                total_bytes += downloads_info.raw.size;
+                downloads.push(RuntimeDownloadTask {
+                    url: downloads_info.raw.url.clone(),
+                    path,
+                    sha1: downloads_info.raw.sha1.clone(),
+                    size: downloads_info.raw.size,
+                    executable: file.executable,
+                });
+            }
+            "link" => {
+                if let Some(target) = &file.target {
+                    links.push((path, target.clone()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let total = downloads.len() as u32;
+    let completed = Arc::new(AtomicU32::new(0));
+    let downloaded_bytes = Arc::new(AtomicU64::new(0));
+    emit_progress(app_handle, format!("Downloading {} (0/{})", component, total), 0, total, 0, total_bytes);
+
+    let results: Vec<Result<(), Box<dyn Error + Send + Sync>>> = stream::iter(downloads)
+        .map(|task| {
+            let completed = Arc::clone(&completed);
+            let downloaded_bytes = Arc::clone(&downloaded_bytes);
+            let app_handle = app_handle.cloned();
+            async move {
+                download_file(&task.url, &task.path, Some(&task.sha1)).await
+                    .map_err(|e| format!("Failed to download {}: {}", task.path.display(), e))?;
+
+                #[cfg(unix)]
+                if task.executable {
+                    use std::os::unix::fs::PermissionsExt;
+                    fs::set_permissions(&task.path, fs::Permissions::from_mode(0o755))?;
+                }
+
+                let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
+                let bytes = downloaded_bytes.fetch_add(task.size, Ordering::SeqCst) + task.size;
+                if done % 10 == 0 || done == total {
+                    emit_progress(app_handle.as_ref(), format!("Downloading {} ({}/{})", component, done, total), done, total, bytes, total_bytes);
+                }
+                Ok(())
+            }
+        })
+        .buffer_unordered(CONCURRENT_DOWNLOADS)
+        .collect()
+        .await;
+
+    save_verification_cache();
+    for result in results {
+        result?;
+    }
+
+    // Links point at files of the same runtime, Windows runtimes don't use them
+    #[cfg(unix)]
+    for (path, target) in links {
+        if fs::symlink_metadata(&path).is_ok() {
+            let _ = fs::remove_file(&path);
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        std::os::unix::fs::symlink(&target, &path)?;
+    }
+    #[cfg(not(unix))]
+    let _ = links;
+
+    // Written last, a runtime without it never finished installing
+    fs::write(install_dir.join(RUNTIME_MANIFEST_FILE), &manifest_bytes)?;
+
+    let java_path = find_java_binary(&install_dir)
+        .ok_or("Failed to locate java binary in the Mojang runtime")?;
+    Ok(java_path)
+}
+
+/// Install the runtime a version asks for: its Mojang component, or Temurin of the same
+/// major version where Mojang has no build for this platform
+pub async fn install_for_version(java_version: &versions::JavaVersion, app_handle: Option<&AppHandle>) -> Result<PathBuf, String> {
+    match install_mojang_runtime(&java_version.component, app_handle).await {
+        Ok(java_path) => Ok(java_path),
+        Err(e) => {
+            log::warn!("Mojang runtime {} unavailable ({}), installing Temurin {} instead", java_version.component, e, java_version.major_version);
+            download_java(java_version.major_version as u32, app_handle)
+                .await
+                .map_err(|e| format!("Failed to install Java {}: {}", java_version.major_version, e))
+        }
+    }
+}
+
+/// Install the latest Temurin JRE of a major version from Adoptium, checking the archive's
+/// SHA-256 and streaming progress while it downloads. Returns the java binary.
+pub async fn download_java(version: u32, app_handle: Option<&AppHandle>) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+    let install_dir = get_java_dir().join(format!("temurin-{}", version));
 
-pub async fn download_java(version: u32) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
-    let install_dir = get_minecraft_dir().join("java").join(format!("temurin-{}", version));
-    
     // Check if already installed
     if let Some(binary) = find_java_binary(&install_dir) {
         return Ok(binary);
@@ -31,43 +702,65 @@ pub async fn download_java(version: u32) -> Result<PathBuf, Box<dyn Error + Send
     };
 
     let url = format!(
-        "https://api.adoptium.net/v3/binary/latest/{}/ga/{}/{}/jre/hotspot/normal/eclipse",
-        version, os, arch
+        "{}/assets/latest/{}/hotspot?architecture={}&image_type=jre&os={}&vendor=eclipse",
+        ADOPTIUM_API, version, arch, os
     );
 
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&url)
-        .header("User-Agent", format!("PaletheaLauncher/{}", super::get_launcher_version()))
-        .send()
-        .await?;
+    let client = http_client()?;
+    let response = client.get(&url).send().await?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to look up Java {}: {}", version, response.status()).into());
+    }
+    let assets: Vec<AdoptiumAsset> = response.json().await?;
+    let asset = assets.into_iter().next()
+        .ok_or_else(|| format!("Adoptium has no Java {} JRE for {}-{}", version, os, arch))?;
+    let package = asset.binary.package;
+    log::info!("Downloading {} ({})", asset.release_name, package.name);
 
+    let mut response = client.get(&package.link).send().await?;
     if !response.status().is_success() {
         return Err(format!("Failed to download Java {}: {}", version, response.status()).into());
     }
 
-    let bytes = response.bytes().await?;
-    let temp_dir = std::env::temp_dir();
-    let archive_name = if os == "windows" {
-        format!("temurin-{}-{}.zip", version, os)
-    } else {
-        format!("temurin-{}-{}.tar.gz", version, os)
-    };
-    let archive_path = temp_dir.join(archive_name);
-    fs::write(&archive_path, &bytes)?;
+    let archive_path = std::env::temp_dir().join(&package.name);
+    let mut archive_file = fs::File::create(&archive_path)?;
+    let mut hasher = Sha256::new();
+    let mut downloaded = 0u64;
+    let mut last_percent = 0u64;
+    let stage = format!("Downloading Java {}", version);
+    emit_progress(app_handle, stage.clone(), 0, 1, 0, package.size);
+
+    while let Some(chunk) = response.chunk().await? {
+        hasher.update(&chunk);
+        archive_file.write_all(&chunk)?;
+        downloaded += chunk.len() as u64;
+
+        let percent = if package.size > 0 { downloaded * 100 / package.size } else { 0 };
+        if percent > last_percent {
+            last_percent = percent;
+            emit_progress(app_handle, stage.clone(), 0, 1, downloaded, package.size);
+        }
+    }
+    drop(archive_file);
+
+    let checksum = format!("{:x}", hasher.finalize());
+    if !checksum.eq_ignore_ascii_case(&package.checksum) {
+        let _ = fs::remove_file(&archive_path);
+        return Err(format!("Checksum verification failed for Java {}: expected {}, got {}", version, package.checksum, checksum).into());
+    }
 
-    let install_dir = get_minecraft_dir().join("java").join(format!("temurin-{}", version));
     if install_dir.exists() {
         let _ = fs::remove_dir_all(&install_dir);
     }
     fs::create_dir_all(&install_dir)?;
 
-    if os == "windows" {
+    if package.name.ends_with(".zip") {
         let file = fs::File::open(&archive_path)?;
         let mut archive = zip::ZipArchive::new(file)?;
         for i in 0..archive.len() {
             let mut entry = archive.by_index(i)?;
-            let out_path = install_dir.join(entry.name());
+            let Some(relative) = entry.enclosed_name() else { continue };
+            let out_path = install_dir.join(relative);
             if entry.is_dir() {
                 fs::create_dir_all(&out_path)?;
             } else {
@@ -89,29 +782,7 @@ pub async fn download_java(version: u32) -> Result<PathBuf, Box<dyn Error + Send
         .ok_or("Failed to locate java binary after extraction")?;
 
     let _ = fs::remove_file(&archive_path);
+    emit_progress(app_handle, stage, 1, 1, downloaded, package.size);
 
     Ok(java_path)
 }
-
-fn find_java_binary(install_dir: &PathBuf) -> Option<PathBuf> {
-    let java_name = if cfg!(target_os = "windows") { "java.exe" } else { "java" };
-
-    let direct = install_dir.join("bin").join(java_name);
-    if direct.exists() {
-        return Some(direct);
-    }
-
-    if let Ok(entries) = fs::read_dir(install_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                let candidate = path.join("bin").join(java_name);
-                if candidate.exists() {
-                    return Some(candidate);
-                }
-            }
-        }
-    }
-
-    None
-}
@@ -0,0 +1,190 @@
+use crate::minecraft::files;
+use crate::minecraft::hooks;
+use crate::minecraft::instances::{Instance, ModLoader};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::LazyLock;
+
+/// Flags that pick the garbage collector, only one of them may be active
+const GC_FLAGS: &[&str] = &[
+    "-XX:+UseG1GC",
+    "-XX:+UseZGC",
+    "-XX:+UseShenandoahGC",
+    "-XX:+UseParallelGC",
+    "-XX:+UseParallelOldGC",
+    "-XX:+UseSerialGC",
+    "-XX:+UseConcMarkSweepGC",
+    "-XX:+UseEpsilonGC",
+];
+
+/// Smallest heap auto memory hands out, and the most it ever gives a single instance
+const AUTO_MEMORY_MIN_MB: u32 = 1024;
+const AUTO_MEMORY_MAX_MB: u32 = 16384;
+
+/// Named set of GC and tuning flags an instance launches with
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum JvmProfile {
+    /// G1 tuned for modpacks, the launcher's long-standing default
+    #[default]
+    G1,
+    /// Generational ZGC, Java 21+
+    ZgcGenerational,
+    Shenandoah,
+    /// No extra flags, for users who bring their own
+    Minimal,
+}
+
+impl JvmProfile {
+    pub fn args(self, java_major: Option<u32>) -> Result<Vec<String>, String> {
+        let args: Vec<&str> = match self {
+            JvmProfile::G1 => vec![
+                "-XX:+UseG1GC",
+                "-XX:+UnlockExperimentalVMOptions",
+                "-XX:G1NewSizePercent=20",
+                "-XX:G1ReservePercent=20",
+                "-XX:MaxGCPauseMillis=50",
+                "-XX:G1HeapRegionSize=32M",
+            ],
+            JvmProfile::ZgcGenerational => match java_major {
+                Some(major) if major < 21 => {
+                    return Err(format!("The ZGC generational profile needs Java 21 or newer, this instance uses Java {}", major));
+                }
+                // Generational is the only mode from 23 on and the flag is deprecated
+                Some(major) if major >= 23 => vec!["-XX:+UseZGC"],
+                _ => vec!["-XX:+UseZGC", "-XX:+ZGenerational"],
+            },
+            JvmProfile::Shenandoah => vec!["-XX:+UseShenandoahGC"],
+            JvmProfile::Minimal => vec![],
+        };
+        Ok(args.into_iter().map(String::from).collect())
+    }
+}
+
+/// Collectors selected by a list of arguments, each listed once
+pub fn selected_collectors(args: &[String]) -> Vec<&str> {
+    let mut collectors: Vec<&str> = Vec::new();
+    for arg in args {
+        if let Some(flag) = GC_FLAGS.iter().find(|f| **f == arg.as_str()) {
+            if !collectors.contains(flag) {
+                collectors.push(flag);
+            }
+        }
+    }
+    collectors
+}
+
+/// The instance's profile flags followed by its own JVM arguments (split with shell quoting).
+/// Without an explicit profile the G1 set is left out when the custom arguments choose a
+/// collector themselves. Fails if more than one collector ends up selected.
+pub fn resolve_args(instance: &Instance, java_major: Option<u32>) -> Result<Vec<String>, String> {
+    let custom = hooks::split_command(instance.jvm_args.as_deref().unwrap_or(""));
+    let profile = match instance.jvm_profile {
+        Some(profile) => profile,
+        None if !selected_collectors(&custom).is_empty() => JvmProfile::Minimal,
+        None => JvmProfile::G1,
+    };
+
+    let mut args = profile.args(java_major)?;
+    args.extend(custom);
+
+    let collectors = selected_collectors(&args);
+    if collectors.len() > 1 {
+        return Err(format!(
+            "Conflicting garbage collector flags in the JVM arguments: {}. Remove the extra flag or switch the instance to the Minimal profile.",
+            collectors.join(", ")
+        ));
+    }
+    Ok(args)
+}
+
+static TOTAL_MEMORY_MB: LazyLock<Option<u32>> = LazyLock::new(detect_total_memory_mb);
+
+fn detect_total_memory_mb() -> Option<u32> {
+    if cfg!(target_os = "linux") {
+        // "MemTotal:       16308704 kB"
+        let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+        let line = meminfo.lines().find(|l| l.starts_with("MemTotal:"))?;
+        let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        return Some((kb / 1024) as u32);
+    }
+
+    let output = if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("powershell");
+        command.args(["-NoProfile", "-Command", "(Get-CimInstance Win32_ComputerSystem).TotalPhysicalMemory"]);
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            command.creation_flags(0x08000000);
+        }
+        command.output()
+    } else {
+        std::process::Command::new("sysctl").args(["-n", "hw.memsize"]).output()
+    };
+
+    let bytes: u64 = String::from_utf8_lossy(&output.ok()?.stdout).trim().parse().ok()?;
+    Some((bytes / 1024 / 1024) as u32)
+}
+
+/// Physical memory of this machine in MB, None if it couldn't be read
+pub fn total_memory_mb() -> Option<u32> {
+    *TOTAL_MEMORY_MB
+}
+
+/// Enabled mod jars in the instance's mods folder
+pub fn count_mods(instance: &Instance) -> u32 {
+    let Ok(entries) = fs::read_dir(files::get_mods_dir(instance)) else { return 0 };
+    entries.flatten()
+        .filter(|e| e.file_name().to_string_lossy().to_lowercase().ends_with(".jar"))
+        .count() as u32
+}
+
+/// Heap sizing chosen by auto memory
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MemoryRecommendation {
+    pub total_memory_mb: Option<u32>,
+    pub mod_count: u32,
+    pub memory_min: u32,
+    pub memory_max: u32,
+}
+
+/// Size the heap from the loader, the number of mods and the machine's RAM. Capped at half
+/// the RAM, leaving at least 2 GB for the system where there's room for it.
+pub fn recommend_memory(instance: &Instance) -> MemoryRecommendation {
+    let mod_count = count_mods(instance);
+    let (base, per_mod) = match instance.mod_loader {
+        ModLoader::Vanilla => (2048, 0),
+        ModLoader::Fabric | ModLoader::Quilt => (2048, 24),
+        ModLoader::Forge | ModLoader::NeoForge => (3072, 40),
+    };
+    let wanted = base + mod_count.saturating_mul(per_mod);
+
+    let total_memory_mb = total_memory_mb();
+    let ceiling = match total_memory_mb {
+        Some(total) => (total / 2).min(total.saturating_sub(2048)).clamp(AUTO_MEMORY_MIN_MB, AUTO_MEMORY_MAX_MB),
+        None => 4096,
+    };
+
+    // Whole 256 MB steps
+    let memory_max = wanted.clamp(AUTO_MEMORY_MIN_MB, ceiling) / 256 * 256;
+    MemoryRecommendation {
+        total_memory_mb,
+        mod_count,
+        memory_min: memory_max.min(1024),
+        memory_max,
+    }
+}
+
+/// -Xms and -Xmx for the instance in MB, from auto memory or its own settings
+pub fn memory_for(instance: &Instance) -> (u32, u32) {
+    if instance.auto_memory {
+        let recommendation = recommend_memory(instance);
+        log::info!(
+            "Auto memory: {} MB for {} mods ({} MB RAM)",
+            recommendation.memory_max,
+            recommendation.mod_count,
+            recommendation.total_memory_mb.map_or("unknown".to_string(), |m| m.to_string())
+        );
+        return (recommendation.memory_min, recommendation.memory_max);
+    }
+    (instance.memory_min.unwrap_or(512), instance.memory_max.unwrap_or(4096))
+}
@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+const LAN_GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 2, 60);
+const LAN_PORT: u16 = 4445;
+/// The game announces every 1.5s, drop games that missed a few announcements
+const EXPIRE_AFTER: Duration = Duration::from_secs(6);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LanGame {
+    pub motd: String,
+    /// Address to connect to, e.g. 192.168.1.20:51234
+    pub address: String,
+    pub host: String,
+    pub port: u16,
+    pub first_seen: u64,
+    pub last_seen: u64,
+}
+
+static LAN_GAMES: LazyLock<Mutex<HashMap<String, (LanGame, std::time::Instant)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static LAN_LISTENER: LazyLock<Mutex<Option<JoinHandle<()>>>> = LazyLock::new(|| Mutex::new(None));
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Parse "[MOTD]A world[/MOTD][AD]51234[/AD]"
+fn parse_announcement(message: &str) -> Option<(String, u16)> {
+    fn between<'a>(s: &'a str, start: &str, end: &str) -> Option<&'a str> {
+        let from = s.find(start)? + start.len();
+        let to = from + s[from..].find(end)?;
+        Some(&s[from..to])
+    }
+
+    let motd = between(message, "[MOTD]", "[/MOTD]").unwrap_or("Minecraft LAN Game");
+    let ad = between(message, "[AD]", "[/AD]")?;
+    // Very old versions announce "host:port" instead of just the port
+    let port = ad.rsplit(':').next()?.trim().parse().ok()?;
+    Some((motd.to_string(), port))
+}
+
+fn bind_multicast() -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    // The game itself listens on the same port while the multiplayer screen is open
+    socket.set_reuse_address(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, LAN_PORT)).into())?;
+    socket.join_multicast_v4(&LAN_GROUP, &Ipv4Addr::UNSPECIFIED)?;
+    socket.set_nonblocking(true)?;
+    UdpSocket::from_std(socket.into())
+}
+
+/// Remove games that stopped announcing, emitting "lan-game-expired" for each
+fn expire_games(app_handle: &AppHandle) {
+    let expired: Vec<LanGame> = match LAN_GAMES.lock() {
+        Ok(mut games) => {
+            let keys: Vec<String> = games.iter()
+                .filter(|(_, (_, seen))| seen.elapsed() > EXPIRE_AFTER)
+                .map(|(k, _)| k.clone())
+                .collect();
+            keys.iter().filter_map(|k| games.remove(k)).map(|(g, _)| g).collect()
+        }
+        Err(_) => return,
+    };
+
+    for game in expired {
+        let _ = app_handle.emit("lan-game-expired", game);
+    }
+}
+
+fn record_game(app_handle: &AppHandle, motd: String, host: String, port: u16) {
+    let address = if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    };
+
+    let (game, notify) = {
+        let mut games = match LAN_GAMES.lock() {
+            Ok(g) => g,
+            Err(_) => return,
+        };
+        let now = now_secs();
+        let is_new = !games.contains_key(&address);
+        let changed = games.get(&address).map(|(g, _)| g.motd != motd).unwrap_or(false);
+        let first_seen = games.get(&address).map(|(g, _)| g.first_seen).unwrap_or(now);
+
+        let game = LanGame {
+            motd,
+            address: address.clone(),
+            host,
+            port,
+            first_seen,
+            last_seen: now,
+        };
+        games.insert(address, (game.clone(), std::time::Instant::now()));
+        (game, is_new || changed)
+    };
+
+    if notify {
+        let _ = app_handle.emit("lan-game-found", game);
+    }
+}
+
+async fn listen(app_handle: AppHandle, socket: UdpSocket) {
+    let mut buf = [0u8; 1024];
+    loop {
+        match tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut buf)).await {
+            Ok(Ok((len, from))) => {
+                let message = String::from_utf8_lossy(&buf[..len]);
+                if let Some((motd, port)) = parse_announcement(&message) {
+                    record_game(&app_handle, motd, from.ip().to_string(), port);
+                }
+            }
+            Ok(Err(e)) => {
+                log::warn!("LAN discovery receive failed: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            // Timeout, just fall through to expiry
+            Err(_) => {}
+        }
+        expire_games(&app_handle);
+    }
+}
+
+/// Start listening for LAN game announcements. Does nothing if already running.
+pub fn start_discovery(app_handle: AppHandle) -> Result<(), String> {
+    let mut listener = LAN_LISTENER.lock().map_err(|_| "LAN listener state corrupted")?;
+    if listener.as_ref().map(|h| !h.is_finished()).unwrap_or(false) {
+        return Ok(());
+    }
+
+    let socket = bind_multicast().map_err(|e| format!("Failed to listen for LAN games: {}", e))?;
+    *listener = Some(tokio::spawn(listen(app_handle, socket)));
+    Ok(())
+}
+
+/// Stop the listener and forget all discovered games
+pub fn stop_discovery() {
+    if let Ok(mut listener) = LAN_LISTENER.lock() {
+        if let Some(handle) = listener.take() {
+            handle.abort();
+        }
+    }
+    if let Ok(mut games) = LAN_GAMES.lock() {
+        games.clear();
+    }
+}
+
+/// Currently announced LAN games, most recently started first
+pub fn get_lan_games() -> Vec<LanGame> {
+    let mut games: Vec<LanGame> = match LAN_GAMES.lock() {
+        Ok(games) => games.values()
+            .filter(|(_, seen)| seen.elapsed() <= EXPIRE_AFTER)
+            .map(|(g, _)| g.clone())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    games.sort_by(|a, b| b.first_seen.cmp(&a.first_seen));
+    games
+}
+
+/// Look up a discovered game by its address
+pub fn find_lan_game(address: &str) -> Option<LanGame> {
+    get_lan_games().into_iter().find(|g| g.address == address)
+}
@@ -3,12 +3,17 @@ use crate::minecraft::instances::{Instance, ModLoader};
 use crate::minecraft::versions::{self, should_use_library, VersionDetails};
 use crate::minecraft::settings;
 use crate::minecraft::fabric;
-use std::fs::{self, File};
-use std::io::{Read, Write};
+use crate::minecraft::forge;
+use crate::minecraft::quilt;
+use crate::minecraft::natives;
+use crate::minecraft::hooks;
+use crate::minecraft::java::{self, get_java_major};
+use crate::minecraft::jvm;
+use std::fs;
 use std::path::PathBuf;
 use std::process::Command;
-use zip::ZipArchive;
 use tauri::Emitter;
+use serde::{Deserialize, Serialize};
 
 // ----------
 // CommandExt trait for hiding console windows on Windows
@@ -102,133 +107,19 @@ pub fn find_java() -> Option<PathBuf> {
     None
 }
 
-fn get_java_major(java_path: &PathBuf) -> Option<u32> {
-    let output = Command::new(java_path)
-        .arg("-version")
-        .hide_console()
-        .output()
-        .ok()?;
-
-    let mut combined = String::new();
-    combined.push_str(&String::from_utf8_lossy(&output.stderr));
-    combined.push_str(&String::from_utf8_lossy(&output.stdout));
-
-    let version_line = combined.lines().next()?;
-    let start = version_line.find('"')? + 1;
-    let end = version_line[start..].find('"')? + start;
-    let raw_version = &version_line[start..end];
-
-    let major = if raw_version.starts_with("1.") {
-        raw_version.split('.').nth(1)?.parse().ok()?
-    } else {
-        raw_version.split('.').next()?.parse().ok()?
-    };
-
-    Some(major)
-}
-
 fn find_java8() -> Option<PathBuf> {
-    if let Ok(java_home) = std::env::var("JAVA_HOME") {
-        let java_path = PathBuf::from(&java_home).join("bin").join(if cfg!(target_os = "windows") { "java.exe" } else { "java" });
-        if java_path.exists() {
-            if let Some(major) = get_java_major(&java_path) {
-                if major <= 8 {
-                    return Some(java_path);
-                }
-            }
-        }
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        let candidates = ["/usr/lib/jvm", "/usr/java", "/opt/java"];
-        for base in candidates {
-            if let Ok(entries) = fs::read_dir(base) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_lowercase();
-                    if !name.contains("1.8") && !name.contains("java-8") && !name.contains("jdk8") {
-                        continue;
-                    }
-                    let java_path = path.join("bin").join("java");
-                    if java_path.exists() {
-                        if let Some(major) = get_java_major(&java_path) {
-                            if major <= 8 {
-                                return Some(java_path);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    None
+    java::list_runtimes()
+        .into_iter()
+        .find(|r| r.major_version.map_or(false, |major| major <= 8))
+        .map(|r| PathBuf::from(r.path))
 }
 
 /// Find a Java installation with a specific major version
 fn find_java_by_version(required_major: u32) -> Option<PathBuf> {
-    let java_name = if cfg!(target_os = "windows") { "java.exe" } else { "java" };
-    
-    // Common Java installation paths
-    let common_paths = if cfg!(target_os = "windows") {
-        vec![
-            "C:\\Program Files\\Java",
-            "C:\\Program Files (x86)\\Java",
-            "C:\\Program Files\\Eclipse Adoptium",
-            "C:\\Program Files\\Microsoft",
-            "C:\\Program Files\\Zulu",
-            "C:\\Program Files\\BellSoft",
-        ]
-    } else if cfg!(target_os = "macos") {
-        vec![
-            "/Library/Java/JavaVirtualMachines",
-            "/usr/local/opt/openjdk/bin",
-            "/opt/homebrew/opt/openjdk",
-        ]
-    } else {
-        vec![
-            "/usr/lib/jvm",
-            "/usr/java",
-            "/opt/java",
-        ]
-    };
-    
-    let mut candidates: Vec<(PathBuf, u32)> = Vec::new();
-    
-    // Check JAVA_HOME first
-    if let Ok(java_home) = std::env::var("JAVA_HOME") {
-        let java_path = PathBuf::from(&java_home).join("bin").join(java_name);
-        if java_path.exists() {
-            if let Some(major) = get_java_major(&java_path) {
-                candidates.push((java_path, major));
-            }
-        }
-    }
-    
-    // Search common paths
-    for base_path in common_paths {
-        let base = PathBuf::from(base_path);
-        if base.exists() {
-            if let Ok(entries) = fs::read_dir(&base) {
-                for entry in entries.flatten() {
-                    // Check both direct bin/java and JDK structure (e.g., jdk-21/Contents/Home/bin/java on macOS)
-                    let paths_to_check = vec![
-                        entry.path().join("bin").join(java_name),
-                        entry.path().join("Contents/Home/bin").join(java_name),
-                    ];
-                    
-                    for java_path in paths_to_check {
-                        if java_path.exists() {
-                            if let Some(major) = get_java_major(&java_path) {
-                                candidates.push((java_path, major));
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
+    let candidates: Vec<(PathBuf, u32)> = java::list_runtimes()
+        .into_iter()
+        .filter_map(|r| Some((PathBuf::from(r.path), r.major_version?)))
+        .collect();
     
     // First try to find exact match
     for (path, major) in &candidates {
@@ -258,6 +149,13 @@ fn find_java_by_version(required_major: u32) -> Option<PathBuf> {
 }
 
 fn select_java_for_launch(instance: &Instance, version_details: &VersionDetails) -> Result<PathBuf, String> {
+    // A pinned runtime wins over everything else and must still exist
+    if let Some(runtime_id) = instance.java_runtime.as_deref().filter(|id| !id.is_empty()) {
+        let runtime = java::find_runtime(runtime_id)
+            .ok_or_else(|| format!("Java runtime {} pinned by this instance is no longer installed", runtime_id))?;
+        return Ok(PathBuf::from(runtime.path));
+    }
+
     // If instance has a specific Java path set, use that
     if let Some(java_path) = &instance.java_path {
         let path = PathBuf::from(java_path);
@@ -330,19 +228,56 @@ fn select_java_for_launch(instance: &Instance, version_details: &VersionDetails)
     Ok(requested)
 }
 
-/// Extract identity (group:artifact[:classifier]) from maven name for deduplication
-/// We exclude version so that different versions of the same library are deduplicated,
-/// but we include classifier so that native JARs are preserved.
-fn get_lib_identity(name: &str) -> String {
-    let parts: Vec<&str> = name.split(':').collect();
-    if parts.len() >= 4 {
-        // group:artifact:version:classifier -> group:artifact:classifier
-        format!("{}:{}:{}", parts[0], parts[1], parts[3])
-    } else if parts.len() >= 2 {
-        // group:artifact:version -> group:artifact
-        format!("{}:{}", parts[0], parts[1])
+/// Installers are stricter than the game, Java 8 era installers break on anything newer
+fn installer_accepts(major: u32, required_major: u32) -> bool {
+    if required_major <= 8 {
+        major == 8
     } else {
-        name.to_string()
+        major >= required_major
+    }
+}
+
+/// Java for running a Forge or NeoForge installer for this version. Goes through the launch
+/// selection, then the other installed runtimes, and downloads a managed runtime when nothing
+/// installed qualifies.
+pub async fn select_java_for_installer(instance: &Instance, version_details: &VersionDetails, app_handle: Option<&tauri::AppHandle>) -> Result<PathBuf, String> {
+    let required_major = version_details.java_version.as_ref().map(|j| j.major_version as u32);
+
+    let selected = {
+        let instance = instance.clone();
+        let version_details = version_details.clone();
+        tokio::task::spawn_blocking(move || {
+            let java_path = select_java_for_launch(&instance, &version_details).ok()?;
+            let Some(required_major) = required_major else { return Some(java_path) };
+            match get_java_major(&java_path) {
+                Some(major) if installer_accepts(major, required_major) => return Some(java_path),
+                major => {
+                    log::info!("{} (Java {:?}) can't run the installer for {}, which needs Java {}", java_path.display(), major, version_details.id, required_major);
+                }
+            }
+
+            // Another installed runtime may still qualify, take the closest version
+            java::list_runtimes().into_iter()
+                .filter_map(|runtime| Some((runtime.major_version?, runtime.path)))
+                .filter(|(major, _)| installer_accepts(*major, required_major))
+                .min_by_key(|(major, _)| *major)
+                .map(|(major, path)| {
+                    log::info!("Using installed Java {} at {} for the installer", major, path);
+                    PathBuf::from(path)
+                })
+        }).await.map_err(|e| e.to_string())?
+    };
+
+    if let Some(java_path) = selected {
+        return Ok(java_path);
+    }
+
+    match &version_details.java_version {
+        Some(java_version) => {
+            log::info!("Installing Java {} to run the installer for {}", java_version.major_version, version_details.id);
+            java::install_for_version(java_version, app_handle).await
+        }
+        None => Err(format!("No suitable Java found to run the installer for {}", version_details.id)),
     }
 }
 
@@ -370,19 +305,12 @@ pub fn build_classpath(version_details: &VersionDetails) -> String {
             }
             
             // Also include native classifier JARs if they exist for this OS
-            if let Some(natives) = &library.natives {
-                let os_name = versions::get_os_name();
-                if let Some(classifier_key) = natives.get(os_name) {
-                    // Replace ${arch} placeholder for the key if present
-                    let arch = if cfg!(target_arch = "x86_64") { "64" } else { "32" };
-                    let actual_key = classifier_key.replace("${arch}", arch);
-                    
-                    if let Some(classifiers) = &downloads.classifiers {
-                        if let Some(native_artifact) = classifiers.get(&actual_key) {
-                            let lib_path = libraries_dir.join(&native_artifact.path);
-                            if lib_path.exists() {
-                                classpath_parts.push(lib_path.to_string_lossy().to_string());
-                            }
+            if let Some(actual_key) = versions::native_classifier(library) {
+                if let Some(classifiers) = &downloads.classifiers {
+                    if let Some(native_artifact) = classifiers.get(&actual_key) {
+                        let lib_path = libraries_dir.join(&native_artifact.path);
+                        if lib_path.exists() {
+                            classpath_parts.push(lib_path.to_string_lossy().to_string());
                         }
                     }
                 }
@@ -398,13 +326,84 @@ pub fn build_classpath(version_details: &VersionDetails) -> String {
 
     // Add client JAR
     let client_jar = versions_dir
-        .join(&version_details.id)
-        .join(format!("{}.jar", &version_details.id));
+        .join(version_details.client_jar_id())
+        .join(format!("{}.jar", version_details.client_jar_id()));
     classpath_parts.push(client_jar.to_string_lossy().to_string());
     
     classpath_parts.join(separator)
 }
 
+// ----------
+// QuickPlayTarget
+// Description: Where the game should take the player right after starting
+// ----------
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum QuickPlayTarget {
+    World { folder_name: String },
+    Server { address: String },
+    Realm { realm_id: String },
+}
+
+/// Feature flags to evaluate argument rules with
+fn rule_features(has_custom_resolution: bool, quick_play: Option<&QuickPlayTarget>) -> versions::RuleFeatures {
+    versions::RuleFeatures {
+        has_custom_resolution,
+        has_quick_plays_support: quick_play.is_some(),
+        is_quick_play_singleplayer: matches!(quick_play, Some(QuickPlayTarget::World { .. })),
+        is_quick_play_multiplayer: matches!(quick_play, Some(QuickPlayTarget::Server { .. })),
+        is_quick_play_realms: matches!(quick_play, Some(QuickPlayTarget::Realm { .. })),
+        ..Default::default()
+    }
+}
+
+/// Whether a version understands the --quickPlay* arguments (1.20+)
+pub fn supports_quick_play(version_details: &VersionDetails) -> bool {
+    version_details.arguments.as_ref()
+        .and_then(|a| a.game.as_ref())
+        .map(|args| args.iter().any(|arg| arg.to_string().contains("${quickPlayMultiplayer}")))
+        .unwrap_or(false)
+}
+
+/// Replace the Quick Play placeholders of an argument
+fn process_quick_play_arg(arg: String, quick_play: Option<&QuickPlayTarget>, game_dir: &PathBuf) -> String {
+    let quick_play = match quick_play {
+        Some(q) => q,
+        None => return arg,
+    };
+
+    let mut res = arg.replace("${quickPlayPath}", &game_dir.join("quickPlay").join("log.json").to_string_lossy());
+    res = match quick_play {
+        QuickPlayTarget::World { folder_name } => res.replace("${quickPlaySingleplayer}", folder_name),
+        QuickPlayTarget::Server { address } => res.replace("${quickPlayMultiplayer}", address),
+        QuickPlayTarget::Realm { realm_id } => res.replace("${quickPlayRealms}", realm_id),
+    };
+    res
+}
+
+/// Pre-1.20 versions can only join servers, through --server/--port
+fn legacy_quick_play_args(quick_play: Option<&QuickPlayTarget>) -> Vec<String> {
+    match quick_play {
+        Some(QuickPlayTarget::Server { address }) => match crate::minecraft::ping::parse_address(address) {
+            Ok((host, port)) => vec![
+                "--server".to_string(),
+                host,
+                "--port".to_string(),
+                port.unwrap_or(25565).to_string(),
+            ],
+            Err(e) => {
+                log::warn!("Ignoring invalid Quick Play server address {}: {}", address, e);
+                Vec::new()
+            }
+        },
+        Some(_) => {
+            log::warn!("Quick Play into worlds and realms needs Minecraft 1.20 or newer, ignoring");
+            Vec::new()
+        }
+        None => Vec::new(),
+    }
+}
+
 /// Build game arguments
 pub fn build_game_args(
     version_details: &VersionDetails,
@@ -412,6 +411,7 @@ pub fn build_game_args(
     username: &str,
     access_token: &str,
     uuid: &str,
+    quick_play: Option<&QuickPlayTarget>,
 ) -> Vec<String> {
     let game_dir = instance.get_game_directory();
     let assets_dir = get_assets_dir();
@@ -422,6 +422,15 @@ pub fn build_game_args(
     let resolution_width = instance.resolution_width.unwrap_or(854).to_string();
     let resolution_height = instance.resolution_height.unwrap_or(480).to_string();
     
+    // Pre-1.7 versions read assets from a virtual tree or the resources folder
+    let game_assets = downloader::get_game_assets_dir(version_details, &game_dir).to_string_lossy().to_string();
+    // 1.6 and older take the session as a single token:<access token>:<uuid> argument
+    let auth_session = if access_token.is_empty() {
+        "-".to_string()
+    } else {
+        format!("token:{}:{}", access_token, uuid)
+    };
+    
     // Handle legacy minecraftArguments format
     if let Some(legacy_args) = &version_details.minecraft_arguments {
         let mut args: Vec<String> = legacy_args
@@ -437,6 +446,8 @@ pub fn build_game_args(
                     .replace("${user_type}", "msa")
                     .replace("${version_type}", &version_details.version_type)
                     .replace("${user_properties}", "{}")
+                    .replace("${game_assets}", &game_assets)
+                    .replace("${auth_session}", &auth_session)
             })
             .collect();
         
@@ -447,34 +458,36 @@ pub fn build_game_args(
             args.push("--height".to_string());
             args.push(resolution_height);
         }
+
+        args.extend(legacy_quick_play_args(quick_play));
         
         return args;
     }
     
     // Handle modern arguments format
     let mut args = Vec::new();
-    let os_name = versions::get_os_name();
+    let features = rule_features(has_custom_resolution, quick_play);
     
     if let Some(arguments) = &version_details.arguments {
         if let Some(game_args) = &arguments.game {
             for arg in game_args {
                 // Handle simple string arguments
                 if let Some(s) = arg.as_str() {
-                    let processed = process_arg_string(s, username, &version_details.id, &game_dir, &assets_dir, &asset_index, uuid, access_token, &version_details.version_type, &resolution_width, &resolution_height, None, None, None);
-                    args.push(processed);
+                    let processed = process_arg_string(s, username, &version_details.id, &game_dir, &assets_dir, &asset_index, uuid, access_token, &version_details.version_type, &resolution_width, &resolution_height, None, None, None, Some(&game_assets));
+                    args.push(process_quick_play_arg(processed, quick_play, &game_dir));
                 }
                 // Handle complex arguments with rules
                 else if let Some(obj) = arg.as_object() {
-                    if check_argument_rules(obj, os_name, has_custom_resolution) {
+                    if versions::argument_allowed(obj, &features) {
                         if let Some(value) = obj.get("value") {
                             if let Some(s) = value.as_str() {
-                                let processed = process_arg_string(s, username, &version_details.id, &game_dir, &assets_dir, &asset_index, uuid, access_token, &version_details.version_type, &resolution_width, &resolution_height, None, None, None);
-                                args.push(processed);
+                                let processed = process_arg_string(s, username, &version_details.id, &game_dir, &assets_dir, &asset_index, uuid, access_token, &version_details.version_type, &resolution_width, &resolution_height, None, None, None, Some(&game_assets));
+                                args.push(process_quick_play_arg(processed, quick_play, &game_dir));
                             } else if let Some(arr) = value.as_array() {
                                 for v in arr {
                                     if let Some(s) = v.as_str() {
-                                        let processed = process_arg_string(s, username, &version_details.id, &game_dir, &assets_dir, &asset_index, uuid, access_token, &version_details.version_type, &resolution_width, &resolution_height, None, None, None);
-                                        args.push(processed);
+                                        let processed = process_arg_string(s, username, &version_details.id, &game_dir, &assets_dir, &asset_index, uuid, access_token, &version_details.version_type, &resolution_width, &resolution_height, None, None, None, Some(&game_assets));
+                                        args.push(process_quick_play_arg(processed, quick_play, &game_dir));
                                     }
                                 }
                             }
@@ -485,6 +498,10 @@ pub fn build_game_args(
         }
     }
     
+    if !supports_quick_play(version_details) {
+        args.extend(legacy_quick_play_args(quick_play));
+    }
+    
     // Deduplicate game arguments
     deduplicate_game_args(args)
 }
@@ -505,6 +522,7 @@ fn process_arg_string(
     classpath: Option<&str>,
     natives_dir: Option<&str>,
     library_dir: Option<&str>,
+    game_assets: Option<&str>,
 ) -> String {
     let mut res = s.replace("${auth_player_name}", username)
         .replace("${version_name}", version_id)
@@ -531,59 +549,38 @@ fn process_arg_string(
     if let Some(ld) = library_dir {
         res = res.replace("${library_directory}", ld);
     }
+    if let Some(ga) = game_assets {
+        res = res.replace("${game_assets}", ga);
+    }
     
     res
 }
 
-/// Check if an argument's rules allow it to be used
-fn check_argument_rules(obj: &serde_json::Map<String, serde_json::Value>, os_name: &str, has_custom_resolution: bool) -> bool {
-    if let Some(rules) = obj.get("rules") {
-        if let Some(rules_arr) = rules.as_array() {
-            let mut result = false;
-            for rule in rules_arr {
-                if let Some(rule_obj) = rule.as_object() {
-                    let action = rule_obj.get("action").and_then(|a| a.as_str()).unwrap_or("allow");
-                    let mut applies = true;
-                    
-                    // Check OS rule
-                    if let Some(os) = rule_obj.get("os") {
-                        if let Some(os_obj) = os.as_object() {
-                            if let Some(name) = os_obj.get("name").and_then(|n| n.as_str()) {
-                                applies = name == os_name;
-                            }
-                        }
-                    }
-                    
-                    // Check features
-                    if let Some(features) = rule_obj.get("features") {
-                        if let Some(features_obj) = features.as_object() {
-                            // Skip demo mode arguments
-                            if features_obj.get("is_demo_user").is_some() {
-                                applies = false;
-                            }
-                            // Include custom resolution arguments only if resolution is set
-                            if features_obj.get("has_custom_resolution").is_some() {
-                                applies = has_custom_resolution;
-                            }
-                            // Skip all quick play arguments - we don't support quick play
-                            if features_obj.get("is_quick_play_singleplayer").is_some() ||
-                               features_obj.get("is_quick_play_multiplayer").is_some() ||
-                               features_obj.get("is_quick_play_realms").is_some() ||
-                               features_obj.get("has_quick_plays_support").is_some() {
-                                applies = false;
-                            }
-                        }
-                    }
-                    
-                    if applies {
-                        result = action == "allow";
-                    }
-                }
-            }
-            return result;
-        }
-    }
-    true
+/// JVM argument pointing log4j at the version's logging config. Unless the XML layout
+/// is wanted, the console appender is switched to a plain pattern in a copy of the file,
+/// which a dry run only points at without writing it.
+fn log_config_argument(config: &versions::LoggingConfig, path: &std::path::Path, xml_layout: bool, dry_run: bool) -> Result<String, String> {
+    let plain_path = || {
+        let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        path.with_file_name(format!("{}-plain.xml", stem))
+    };
+    let config_path = if xml_layout {
+        path.to_path_buf()
+    } else if dry_run {
+        plain_path()
+    } else {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read log config: {}", e))?;
+        let layout = regex::Regex::new(r"<(Legacy)?XMLLayout\s*/>").map_err(|e| e.to_string())?;
+        let plain = layout.replace_all(&content, r#"<PatternLayout pattern="[%d{HH:mm:ss}] [%t/%level]: %msg{nolookups}%n" />"#);
+
+        let plain_path = plain_path();
+        fs::write(&plain_path, plain.as_bytes())
+            .map_err(|e| format!("Failed to write log config: {}", e))?;
+        plain_path
+    };
+
+    Ok(config.argument.replace("${path}", &config_path.to_string_lossy()))
 }
 
 /// Build JVM arguments
@@ -591,46 +588,40 @@ pub fn build_jvm_args(
     version_details: &VersionDetails,
     instance: &Instance,
     classpath: &str,
-) -> Vec<String> {
+    natives_dir: &std::path::Path,
+    java_major: Option<u32>,
+) -> Result<Vec<String>, String> {
     let mut args = Vec::new();
-    let natives_dir = instance.get_directory().join("natives");
     let library_dir = crate::minecraft::downloader::get_libraries_dir();
     let game_dir = instance.get_game_directory();
     let assets_dir = crate::minecraft::downloader::get_assets_dir();
     let asset_index = version_details.asset_index.as_ref().map(|a| a.id.clone()).unwrap_or_else(|| "legacy".to_string());
-    let os_name = versions::get_os_name();
 
     // Memory settings
-    let min_mem = instance.memory_min.unwrap_or(512);
-    let max_mem = instance.memory_max.unwrap_or(4096);
+    let (min_mem, max_mem) = jvm::memory_for(instance);
     args.push(format!("-Xms{}M", min_mem));
     args.push(format!("-Xmx{}M", max_mem));
 
-    // Default performance optimizations (G1GC is standard for modpacks)
-    args.push("-XX:+UseG1GC".to_string());
-    args.push("-XX:+UnlockExperimentalVMOptions".to_string());
-    args.push("-XX:G1NewSizePercent=20".to_string());
-    args.push("-XX:G1ReservePercent=20".to_string());
-    args.push("-XX:MaxGCPauseMillis=50".to_string());
-    args.push("-XX:G1HeapRegionSize=32M".to_string());
+    // Profile flags and the instance's own arguments, checked for conflicting collectors
+    let profile_args = jvm::resolve_args(instance, java_major)?;
     
     // Process JVM arguments from version JSON
     if let Some(arguments) = &version_details.arguments {
         if let Some(jvm_args) = &arguments.jvm {
             for arg in jvm_args {
                 if let Some(s) = arg.as_str() {
-                    let processed = process_arg_string(s, "", &version_details.id, &game_dir, &assets_dir, &asset_index, "", "", &version_details.version_type, "854", "480", Some(classpath), Some(&natives_dir.to_string_lossy()), Some(&library_dir.to_string_lossy()));
+                    let processed = process_arg_string(s, "", &version_details.id, &game_dir, &assets_dir, &asset_index, "", "", &version_details.version_type, "854", "480", Some(classpath), Some(&natives_dir.to_string_lossy()), Some(&library_dir.to_string_lossy()), None);
                     args.push(processed);
                 } else if let Some(obj) = arg.as_object() {
-                    if check_argument_rules(obj, os_name, false) {
+                    if versions::argument_allowed(obj, &versions::RuleFeatures::default()) {
                         if let Some(value) = obj.get("value") {
                             if let Some(s) = value.as_str() {
-                                let processed = process_arg_string(s, "", &version_details.id, &game_dir, &assets_dir, &asset_index, "", "", &version_details.version_type, "854", "480", Some(classpath), Some(&natives_dir.to_string_lossy()), Some(&library_dir.to_string_lossy()));
+                                let processed = process_arg_string(s, "", &version_details.id, &game_dir, &assets_dir, &asset_index, "", "", &version_details.version_type, "854", "480", Some(classpath), Some(&natives_dir.to_string_lossy()), Some(&library_dir.to_string_lossy()), None);
                                 args.push(processed);
                             } else if let Some(arr) = value.as_array() {
                                 for v in arr {
                                     if let Some(s) = v.as_str() {
-                                        let processed = process_arg_string(s, "", &version_details.id, &game_dir, &assets_dir, &asset_index, "", "", &version_details.version_type, "854", "480", Some(classpath), Some(&natives_dir.to_string_lossy()), Some(&library_dir.to_string_lossy()));
+                                        let processed = process_arg_string(s, "", &version_details.id, &game_dir, &assets_dir, &asset_index, "", "", &version_details.version_type, "854", "480", Some(classpath), Some(&natives_dir.to_string_lossy()), Some(&library_dir.to_string_lossy()), None);
                                         args.push(processed);
                                     }
                                 }
@@ -640,22 +631,21 @@ pub fn build_jvm_args(
                 }
             }
         }
-    } else {
-        // Fallback for older versions that don't have jvm args in JSON
+    }
+
+    // Older versions have no jvm args in their JSON, and a loader inheriting from
+    // them may only add its own (e.g. Fabric on 1.12)
+    if !args.iter().any(|a| a == "-cp" || a == "-classpath") {
         args.push(format!("-Djava.library.path={}", natives_dir.to_string_lossy()));
         args.push("-cp".to_string());
         args.push(classpath.to_string());
     }
     
-    // Custom JVM args from instance
-    if let Some(custom_args) = &instance.jvm_args {
-        for arg in custom_args.split_whitespace() {
-            args.push(arg.to_string());
-        }
-    }
+    // Profile and custom JVM args from instance
+    args.extend(profile_args);
     
     // Deduplicate JVM arguments
-    deduplicate_jvm_args(args)
+    Ok(deduplicate_jvm_args(args))
 }
 
 fn deduplicate_jvm_args(args: Vec<String>) -> Vec<String> {
@@ -739,22 +729,147 @@ fn deduplicate_game_args(args: Vec<String>) -> Vec<String> {
     final_args
 }
 
-/// Launch Minecraft
-pub async fn launch_game(
+/// Find the installed Forge/NeoForge version ID for an instance
+fn find_forge_version_id(instance: &Instance) -> Option<String> {
+    let loader_version = instance.mod_loader_version.as_ref()?;
+    let mc_version = &instance.version_id;
+    let mut possible_ids = Vec::new();
+
+    // The ID the installer created, recorded in forge.json or neoforge.json
+    let loader_info = match instance.mod_loader {
+        ModLoader::NeoForge => forge::load_neoforge_info(instance),
+        _ => forge::load_forge_info(instance),
+    };
+    if let Some(version_id) = loader_info.and_then(|info| info.version_id) {
+        possible_ids.push(version_id);
+    }
+
+    // Installs made before the ID was recorded
+    if instance.mod_loader == ModLoader::Forge {
+        possible_ids.push(format!("{}-forge-{}", mc_version, loader_version));
+        possible_ids.push(format!("{}-forge{}", mc_version, loader_version));
+    } else {
+        possible_ids.push(format!("neoforge-{}", loader_version));
+        possible_ids.push(loader_version.clone());
+        possible_ids.push(format!("{}-neoforge-{}", mc_version, loader_version));
+    }
+
+    possible_ids.into_iter()
+        .find(|id| get_versions_dir().join(id).join(format!("{}.json", id)).exists())
+}
+
+/// Version JSON of the instance's mod loader, inheriting from the game version
+fn loader_version_json(instance: &Instance) -> Option<serde_json::Value> {
+    match instance.mod_loader {
+        ModLoader::Forge | ModLoader::NeoForge => {
+            let id = find_forge_version_id(instance)?;
+            versions::load_version_json(&id)
+                .map_err(|e| log::warn!("{}", e))
+                .ok()
+        }
+        ModLoader::Fabric => fabric::load_fabric_info(instance)
+            .map(|info| fabric::fabric_version_json(&info, &instance.version_id)),
+        ModLoader::Quilt => quilt::load_quilt_info(instance)
+            .map(|info| quilt::quilt_version_json(&info)),
+        ModLoader::Vanilla => None,
+    }
+}
+
+/// Time spent in one launch stage
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LaunchStageTiming {
+    pub stage: String,
+    pub duration_ms: u64,
+}
+
+/// Emitted as "launch-timings" once the game process has been started
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LaunchTimings {
+    pub instance_id: String,
+    pub stages: Vec<LaunchStageTiming>,
+    pub total_ms: u64,
+}
+
+/// Measures consecutive launch stages
+struct StageTimer {
+    started: std::time::Instant,
+    last: std::time::Instant,
+    stages: Vec<LaunchStageTiming>,
+}
+
+impl StageTimer {
+    fn new() -> Self {
+        let now = std::time::Instant::now();
+        StageTimer { started: now, last: now, stages: Vec::new() }
+    }
+
+    /// Record the time since the previous mark under `stage`
+    fn mark(&mut self, stage: &str) {
+        let now = std::time::Instant::now();
+        self.stages.push(LaunchStageTiming {
+            stage: stage.to_string(),
+            duration_ms: now.duration_since(self.last).as_millis() as u64,
+        });
+        self.last = now;
+    }
+
+    fn finish(self, instance_id: &str) -> LaunchTimings {
+        LaunchTimings {
+            instance_id: instance_id.to_string(),
+            stages: self.stages,
+            total_ms: self.started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+/// Everything needed to start the game process
+#[derive(Debug, Clone)]
+pub struct LaunchPlan {
+    pub version_id: String,
+    pub java_path: PathBuf,
+    pub main_class: String,
+    pub classpath: Vec<String>,
+    pub jvm_args: Vec<String>,
+    pub game_args: Vec<String>,
+    pub game_dir: PathBuf,
+    /// Files a dry run found missing, a real launch downloads them instead
+    pub missing_files: Vec<PathBuf>,
+    /// Wrapper command words java is started through, empty without a wrapper
+    pub wrapper: Vec<String>,
+    /// Environment the game starts with on top of the launcher's, placeholders expanded
+    pub env_vars: Vec<(String, String)>,
+}
+
+/// Resolve the version, verify files and build the full command line without starting anything.
+/// A dry run leaves the disk untouched: nothing is downloaded or extracted and missing
+/// files are listed in the plan instead.
+pub async fn prepare_launch(
     instance: &Instance,
     version_details: &VersionDetails,
     username: &str,
     access_token: &str,
     uuid: &str,
+    quick_play: Option<&QuickPlayTarget>,
+    dry_run: bool,
     app_handle: &tauri::AppHandle,
-) -> Result<std::process::Child, String> {
-    // Determine the actual version details to use (may be overridden by mod loader)
+) -> Result<LaunchPlan, String> {
+    prepare_launch_timed(instance, version_details, username, access_token, uuid, quick_play, dry_run, app_handle, &mut StageTimer::new()).await
+}
+
+async fn prepare_launch_timed(
+    instance: &Instance,
+    version_details: &VersionDetails,
+    username: &str,
+    access_token: &str,
+    uuid: &str,
+    quick_play: Option<&QuickPlayTarget>,
+    dry_run: bool,
+    app_handle: &tauri::AppHandle,
+    timer: &mut StageTimer,
+) -> Result<LaunchPlan, String> {
+    // Resolve the mod loader's version JSON on top of the game version, if there is one
     let mut actual_version_details = version_details.clone();
-    
-    // Handle Forge / NeoForge by loading their custom version.json if available
-    if (instance.mod_loader == ModLoader::Forge || instance.mod_loader == ModLoader::NeoForge) && instance.mod_loader_version.is_some() {
-        let loader_version = instance.mod_loader_version.as_ref().unwrap();
-        
+    if let Some(loader_json) = loader_version_json(instance) {
         let _ = app_handle.emit("download-progress", DownloadProgress {
             stage: format!("Merging {} config...", instance.mod_loader.to_string()),
             current: 0,
@@ -763,114 +878,21 @@ pub async fn launch_game(
             total_bytes: None,
             downloaded_bytes: None,
         });
-        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
 
-        let mc_version = &instance.version_id;
-        
-        // Potential IDs for Forge/NeoForge
-        let mut possible_ids = Vec::new();
-        
-        // Check for specific version ID from our metadata
-        let forge_json_path = instance.get_directory().join("forge.json");
-        if forge_json_path.exists() {
-            if let Ok(content) = fs::read_to_string(&forge_json_path) {
-                if let Ok(forge_info) = serde_json::from_str::<serde_json::Value>(&content) {
-                    if let Some(vid) = forge_info.get("version_id").and_then(|v| v.as_str()) {
-                        possible_ids.push(vid.to_string());
-                    }
-                }
-            }
-        }
-
-        if instance.mod_loader == ModLoader::Forge {
-            possible_ids.push(format!("{}-forge-{}", mc_version, loader_version));
-            possible_ids.push(format!("{}-forge{}", mc_version, loader_version));
+        let loader_id = loader_json.get("id").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        // Skip double-merging if the mod loader JSON is already the primary manifest
+        if loader_id == version_details.id {
+            log::debug!("Mod loader ID {} matches primary manifest ID, skipping merge.", loader_id);
         } else {
-            possible_ids.push(format!("neoforge-{}", loader_version));
-            possible_ids.push(loader_version.clone());
-            possible_ids.push(format!("{}-neoforge-{}", mc_version, loader_version));
-        }
-        
-        for id in possible_ids {
-            let json_path = get_versions_dir().join(&id).join(format!("{}.json", id));
-            if json_path.exists() {
-                if let Ok(details) = versions::load_version_details(&json_path) {
-                    // Skip double-merging if the mod loader JSON is already the primary manifest
-                    if details.id == actual_version_details.id {
-                        log::debug!("Mod loader ID {} matches primary manifest ID, skipping merge.", details.id);
-                        break;
-                    }
-
-                    // Update main info
-                    actual_version_details.id = details.id.clone();
-                    actual_version_details.main_class = details.main_class.clone();
-
-                    if details.minecraft_arguments.is_some() {
-                        actual_version_details.minecraft_arguments = details.minecraft_arguments.clone();
-                        // If mod loader has legacy arguments, clear modern ones to avoid conflicts
-                        actual_version_details.arguments = None;
-                    }
-
-                    if details.asset_index.is_some() {
-                        actual_version_details.asset_index = details.asset_index.clone();
-                    }
-                    if details.java_version.is_some() {
-                        actual_version_details.java_version = details.java_version.clone();
-                    }
-                    
-                    // Prepended Forge libraries so they take priority, deduplicating by identity (group:artifact)
-                    let mut combined_libs = details.libraries.clone();
-                    for lib in &actual_version_details.libraries {
-                        let identity = get_lib_identity(&lib.name);
-                        if !combined_libs.iter().any(|l| get_lib_identity(&l.name) == identity) {
-                            combined_libs.push(lib.clone());
-                        }
-                    }
-                    actual_version_details.libraries = combined_libs;
-                    
-                    // Merge arguments if present
-                    if let Some(mod_args) = details.arguments {
-                        let mut merged_args = actual_version_details.arguments.clone().unwrap_or(versions::Arguments { game: None, jvm: None });
-                        
-                        // Game arguments: If mod loader provides a "full" set (detected by --username), replace vanilla's.
-                        // Otherwise, merge them.
-                        if let Some(mod_game) = mod_args.game {
-                            let is_complete_set = mod_game.iter().any(|v| v.as_str().map_or(false, |s| s == "--username"));
-                            
-                            if is_complete_set {
-                                merged_args.game = Some(mod_game);
-                            } else {
-                                let mut game_base = merged_args.game.unwrap_or_default();
-                                game_base.extend(mod_game);
-                                merged_args.game = Some(game_base);
-                            }
-                        }
-                        
-                        // JVM arguments: Usually these are increments (like Forge properties), so we merge.
-                        if let Some(mod_jvm) = mod_args.jvm {
-                            let mut jvm_base = merged_args.jvm.unwrap_or_default();
-                            jvm_base.extend(mod_jvm);
-                            merged_args.jvm = Some(jvm_base);
-                        }
-                        
-                        actual_version_details.arguments = Some(merged_args);
-                        actual_version_details.minecraft_arguments = None;
-                    }
-                    
-                    if let Some(mod_legacy_args) = details.minecraft_arguments {
-                        // For older Forge versions that use the legacy format
-                        // Legacy arguments are usually complete, so we still replace
-                        actual_version_details.minecraft_arguments = Some(mod_legacy_args);
-                        actual_version_details.arguments = None;
-                    }
-                    
-                    log::info!("Successfully merged mod loader version JSON: {}", json_path.display());
-                    break;
-                }
-            }
+            actual_version_details = versions::resolve_version_json(loader_json)?;
+            log::info!("Resolved mod loader version {} on top of {}", loader_id, version_details.id);
         }
     }
 
+    // Swap in ARM64 LWJGL builds where the version JSON only has x86 natives
+    let actual_version_details = natives::apply_overrides(&actual_version_details);
+    timer.mark("resolve_version");
+
     // Ensure client JAR is present and valid (avoid corrupt vanilla jar)
     log::info!("Checking for missing client JAR...");
     let _ = app_handle.emit("download-progress", DownloadProgress {
@@ -881,9 +903,17 @@ pub async fn launch_game(
         total_bytes: None,
         downloaded_bytes: None,
     });
-    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
-    let _ = crate::minecraft::downloader::download_client(&actual_version_details).await
-        .map_err(|e| format!("Failed to download client JAR: {}", e))?;
+    let mut missing_files = Vec::new();
+    if dry_run {
+        let client_jar = downloader::get_client_jar_path(&actual_version_details);
+        if !client_jar.exists() {
+            missing_files.push(client_jar);
+        }
+    } else {
+        let _ = crate::minecraft::downloader::download_client(&actual_version_details).await
+            .map_err(|e| format!("Failed to download client JAR: {}", e))?;
+    }
+    timer.mark("verify_client");
 
     // Ensure all libraries (including mod loader dependencies) are downloaded
     log::info!("Checking for missing libraries...");
@@ -891,16 +921,30 @@ pub async fn launch_game(
         stage: "Verifying libraries...".to_string(),
         current: 0,
         total: 1,
-        percentage: 60.0,
+        percentage: 45.0,
         total_bytes: None,
         downloaded_bytes: None,
     });
-    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
-    let _ = crate::minecraft::downloader::download_libraries(&actual_version_details, Some(app_handle)).await
-        .map_err(|e| format!("Failed to download missing libraries: {}", e))?;
+    let log_config_path = if dry_run {
+        missing_files.extend(downloader::missing_libraries(&actual_version_details));
+        let path = actual_version_details.logging.as_ref()
+            .and_then(|l| l.client.as_ref())
+            .map(downloader::get_log_config_path);
+        if let Some(path) = path.as_ref().filter(|p| !p.exists()) {
+            missing_files.push(path.clone());
+        }
+        path
+    } else {
+        let _ = downloader::download_libraries_in_range(&actual_version_details, Some(app_handle), 45.0, 30.0).await
+            .map_err(|e| format!("Failed to download missing libraries: {}", e))?;
+        downloader::download_log_config(&actual_version_details).await
+            .map_err(|e| format!("Failed to download log config: {}", e))?
+    };
+    timer.mark("verify_libraries");
 
     // Find Java: instance setting > global setting > auto-detect (with legacy Forge handling)
     let java_path = select_java_for_launch(instance, &actual_version_details)?;
+    timer.mark("select_java");
     
     // Build classpath - handle deduplication to avoid "duplicate ASM classes" error
     let _ = app_handle.emit("download-progress", DownloadProgress {
@@ -911,21 +955,11 @@ pub async fn launch_game(
         total_bytes: None,
         downloaded_bytes: None,
     });
-    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
     let mut classpath_elements: Vec<(String, String)> = Vec::new();
     
-    // Determine main class
-    let mut main_class = actual_version_details.main_class.clone();
-    
-    // 1. Handle Fabric mod loader additions (takes priority)
-    if instance.mod_loader == ModLoader::Fabric {
-        if let Some(fabric_info) = fabric::load_fabric_info(instance) {
-            classpath_elements.extend(fabric::get_fabric_classpath(&fabric_info));
-            main_class = fabric_info.launcher_meta.main_class.get_client_class().to_string();
-        }
-    }
-    
-    // 2. Add vanilla / Forge merged libraries
+    let main_class = actual_version_details.main_class.clone();
+
+    // 1. Add the resolved libraries (mod loader libraries come first)
     let libraries_dir = get_libraries_dir();
     for library in &actual_version_details.libraries {
         if !versions::should_use_library(library) {
@@ -942,20 +976,14 @@ pub async fn launch_game(
             }
             
             // Add native classifiers if they exist for this OS
-            if let Some(natives) = &library.natives {
-                let os_name = versions::get_os_name();
-                if let Some(classifier_key) = natives.get(os_name) {
-                    let arch = if cfg!(target_arch = "x86_64") { "64" } else { "32" };
-                    let actual_key = classifier_key.replace("${arch}", arch);
-                    
-                    if let Some(classifiers) = &downloads.classifiers {
-                        if let Some(native_artifact) = classifiers.get(&actual_key) {
-                            let lib_path = libraries_dir.join(&native_artifact.path);
-                            if lib_path.exists() {
-                                // For natives, we use the original name plus the key for deduplication
-                                let name_with_classifier = format!("{}:{}", library.name, actual_key);
-                                classpath_elements.push((name_with_classifier, lib_path.to_string_lossy().to_string()));
-                            }
+            if let Some(actual_key) = versions::native_classifier(library) {
+                if let Some(classifiers) = &downloads.classifiers {
+                    if let Some(native_artifact) = classifiers.get(&actual_key) {
+                        let lib_path = libraries_dir.join(&native_artifact.path);
+                        if lib_path.exists() {
+                            // For natives, we use the original name plus the key for deduplication
+                            let name_with_classifier = format!("{}:{}", library.name, actual_key);
+                            classpath_elements.push((name_with_classifier, lib_path.to_string_lossy().to_string()));
                         }
                     }
                 }
@@ -969,30 +997,55 @@ pub async fn launch_game(
         }
     }
 
-    // 3. Deduplicate elements by maven identity (group:artifact:classifier)
+    // 2. Deduplicate elements by maven identity (group:artifact:classifier)
     // This solves the "duplicate ASM classes found on classpath" error by preferring 
-    // the first version found (which will be Fabric's version if using Fabric)
+    // the first version found (which will be the mod loader's version)
     let mut final_paths = Vec::new();
     let mut seen_identities = std::collections::HashSet::new();
     
     for (name, path) in classpath_elements {
-        let identity = get_lib_identity(&name);
+        let identity = versions::get_lib_identity(&name);
         if !seen_identities.contains(&identity) {
             seen_identities.insert(identity);
             final_paths.push(path);
         }
     }
 
-    // 4. Add the actual game JAR
+    // 3. Add the actual game JAR
     let versions_dir = get_versions_dir();
-    let client_jar = versions_dir.join(&actual_version_details.id).join(format!("{}.jar", &actual_version_details.id));
+    let jar_id = actual_version_details.client_jar_id();
+    let client_jar = versions_dir.join(jar_id).join(format!("{}.jar", jar_id));
     final_paths.push(client_jar.to_string_lossy().to_string());
 
     let separator = if cfg!(target_os = "windows") { ";" } else { ":" };
     let classpath = final_paths.join(separator);
+    timer.mark("build_classpath");
     
+    // Natives are extracted once per version into a shared cache
+    let _ = app_handle.emit("download-progress", DownloadProgress {
+        stage: "Extracting natives...".to_string(),
+        current: 0,
+        total: 0,
+        percentage: 85.0,
+        total_bytes: None,
+        downloaded_bytes: None,
+    });
+    let natives_dir = if dry_run {
+        natives::planned_natives_dir(&actual_version_details)
+    } else {
+        natives::prepare_natives(&actual_version_details)?
+    };
+    timer.mark("extract_natives");
+
     // Build arguments
-    let mut jvm_args = build_jvm_args(&actual_version_details, instance, &classpath);
+    let java_major = get_java_major(&java_path);
+    let mut jvm_args = build_jvm_args(&actual_version_details, instance, &classpath, &natives_dir, java_major)?;
+
+    // Official log4j config, also carries the Log4Shell mitigation for 1.7 - 1.18
+    if let (Some(path), Some(config)) = (&log_config_path, actual_version_details.logging.as_ref().and_then(|l| l.client.as_ref())) {
+        let xml_layout = settings::load_settings().log_xml_layout.unwrap_or(false);
+        jvm_args.push(log_config_argument(config, path, xml_layout, dry_run)?);
+    }
     
     // Performance tricks used by Prism/MultiMC
     #[cfg(target_os = "windows")]
@@ -1001,23 +1054,84 @@ pub async fn launch_game(
         jvm_args.push("-Duser.language=en".to_string());
     }
 
-    let game_args = build_game_args(&actual_version_details, instance, username, access_token, uuid);
+    let game_args = build_game_args(&actual_version_details, instance, username, access_token, uuid, quick_play);
     
     // Create game directory if it doesn't exist
     let game_dir = instance.get_game_directory();
-    fs::create_dir_all(&game_dir)
-        .map_err(|e| format!("Failed to create game directory: {}", e))?;
-    
-    // Create natives directory
-    let natives_dir = instance.get_directory().join("natives");
-    fs::create_dir_all(&natives_dir)
-        .map_err(|e| format!("Failed to create natives directory: {}", e))?;
-    
-    // Extract natives from library JARs
-    extract_natives(&actual_version_details, &natives_dir)?;
+    if !dry_run {
+        fs::create_dir_all(&game_dir)
+            .map_err(|e| format!("Failed to create game directory: {}", e))?;
+
+        // Old versions need their assets laid out by name instead of by hash
+        downloader::prepare_legacy_assets(&actual_version_details, &game_dir)
+            .map_err(|e| format!("Failed to prepare legacy assets: {}", e))?;
+    }
+
+    // Wrapper and environment, so previews and exported scripts match the real launch
+    let launch_hooks = hooks::resolve_hooks(instance);
+    let placeholders = hooks::placeholder_values(instance, &java_path);
+    let wrapper = launch_hooks.wrapper_command.as_deref()
+        .map(|w| hooks::split_command(w).iter()
+            .map(|word| hooks::expand_placeholders(word, &placeholders))
+            .collect::<Vec<_>>())
+        .unwrap_or_default();
+    let mut env_vars: Vec<(String, String)> = placeholders.iter()
+        .map(|(k, v)| (k.to_string(), v.clone()))
+        .collect();
+    env_vars.extend(launch_hooks.env_vars.iter()
+        .map(|(k, v)| (k.clone(), hooks::expand_placeholders(v, &placeholders))));
+    timer.mark("prepare_arguments");
     
+    Ok(LaunchPlan {
+        version_id: actual_version_details.id.clone(),
+        java_path,
+        main_class,
+        classpath: final_paths,
+        jvm_args,
+        game_args,
+        game_dir,
+        missing_files,
+        wrapper,
+        env_vars,
+    })
+}
+
+/// Launch Minecraft. Returns the game process, the Java it runs on and the environment it
+/// was given, for the post-exit hook.
+pub async fn launch_game(
+    instance: &Instance,
+    version_details: &VersionDetails,
+    username: &str,
+    access_token: &str,
+    uuid: &str,
+    quick_play: Option<&QuickPlayTarget>,
+    app_handle: &tauri::AppHandle,
+) -> Result<(std::process::Child, PathBuf, Vec<(String, String)>), String> {
+    let mut timer = StageTimer::new();
+    let LaunchPlan { java_path, main_class, jvm_args, game_args, game_dir, wrapper, env_vars, .. } =
+        prepare_launch_timed(instance, version_details, username, access_token, uuid, quick_play, false, app_handle, &mut timer).await?;
+
+    let launch_hooks = hooks::resolve_hooks(instance);
+    if let Some(pre_launch) = launch_hooks.pre_launch_command.clone() {
+        let _ = app_handle.emit("download-progress", DownloadProgress {
+            stage: "Running pre-launch command...".to_string(),
+            current: 0,
+            total: 0,
+            percentage: 95.0,
+            total_bytes: None,
+            downloaded_bytes: None,
+        });
+        let hook_instance = instance.clone();
+        let hook_java = java_path.clone();
+        let hook_env = env_vars.clone();
+        tokio::task::spawn_blocking(move || hooks::run_hook("pre-launch", &pre_launch, &hook_instance, &hook_java, &hook_env))
+            .await
+            .map_err(|e| e.to_string())??;
+        timer.mark("pre_launch_hook");
+    }
+
     // Log the arguments for debugging
-    log::info!("Game args: {:?}", game_args);
+    log::info!("Game args: {:?}", redact_args(&game_args, access_token));
 
     let _ = app_handle.emit("download-progress", DownloadProgress {
         stage: "Starting process...".to_string(),
@@ -1027,22 +1141,31 @@ pub async fn launch_game(
         total_bytes: None,
         downloaded_bytes: None,
     });
-    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
 
     // Build the full command string for verbose logging
     let full_command = format!(
-        "\"{}\" {} {} {}",
+        "{}\"{}\" {} {} {}",
+        wrapper.iter().map(|w| format!("{} ", w)).collect::<String>(),
         java_path.display(),
         jvm_args.join(" "),
         main_class,
-        game_args.join(" ")
+        redact_args(&game_args, access_token).join(" ")
     );
     println!("\n=== STARTING MINECRAFT ===\n{}\n==========================\n", full_command);
     log::info!("Full launch command: {}", full_command);
     
-    // Build command
-    let mut command = Command::new(&java_path);
+    // Build command, started through the wrapper if one is set
+    let mut command = match wrapper.split_first() {
+        Some((program, wrapper_args)) => {
+            log::info!("Launching through wrapper: {}", wrapper.join(" "));
+            let mut c = Command::new(program);
+            c.args(wrapper_args).arg(&java_path);
+            c
+        }
+        None => Command::new(&java_path),
+    };
     command.current_dir(&game_dir);
+    command.envs(env_vars.iter().map(|(k, v)| (k.as_str(), v.as_str())));
 
     #[cfg(target_os = "windows")]
     {
@@ -1099,7 +1222,7 @@ pub async fn launch_game(
             full_cmd.push_str(&format!(" \"{}\"", arg));
         }
         full_cmd.push_str(&format!(" \"{}\"", main_class));
-        for arg in &game_args {
+        for arg in redact_args(&game_args, access_token) {
             full_cmd.push_str(&format!(" \"{}\"", arg));
         }
         println!("\n=== FULL MINECRAFT LAUNCH COMMAND ===\n{}\n=====================================\n", full_cmd);
@@ -1108,98 +1231,126 @@ pub async fn launch_game(
     // Launch the game
     let child = command.spawn()
         .map_err(|e| format!("Failed to launch Minecraft: {}", e))?;
+    timer.mark("spawn_process");
+
+    let timings = timer.finish(&instance.id);
+    log::info!("Launch prepared in {} ms: {:?}", timings.total_ms, timings.stages);
+    let _ = app_handle.emit("launch-timings", timings);
     
-    Ok(child)
+    Ok((child, java_path, env_vars))
 }
 
-/// Extract native libraries from JARs
-fn extract_natives(version_details: &VersionDetails, natives_dir: &PathBuf) -> Result<(), String> {
-    let libraries_dir = get_libraries_dir();
-    let os_name = versions::get_os_name();
-    
-    for library in &version_details.libraries {
-        if !should_use_library(library) {
-            continue;
-        }
-        
-        // Check if this library has natives for our OS
-        if let Some(natives) = &library.natives {
-            if let Some(classifier_key) = natives.get(os_name) {
-                // Replace ${arch} placeholder
-                let arch = if cfg!(target_arch = "x86_64") { "64" } else { "32" };
-                let classifier_key = classifier_key.replace("${arch}", arch);
-                
-                // Find the native JAR in classifiers
-                if let Some(downloads) = &library.downloads {
-                    if let Some(classifiers) = &downloads.classifiers {
-                        if let Some(native_artifact) = classifiers.get(&classifier_key) {
-                            let native_jar_path = libraries_dir.join(&native_artifact.path);
-                            
-                            if native_jar_path.exists() {
-                                // Get exclusions
-                                let exclusions: Vec<&str> = library.extract
-                                    .as_ref()
-                                    .and_then(|e| e.exclude.as_ref())
-                                    .map(|v| v.iter().map(|s| s.as_str()).collect())
-                                    .unwrap_or_default();
-                                
-                                // Extract the JAR
-                                extract_jar(&native_jar_path, natives_dir, &exclusions)?;
-                            }
-                        }
-                    }
-                }
-            }
-        }
+const REDACTED: &str = "<redacted>";
+
+/// Replace the access token wherever it appears in a value
+fn redact(value: &str, access_token: &str) -> String {
+    if access_token.is_empty() {
+        return value.to_string();
     }
-    
-    Ok(())
+    value.replace(access_token, REDACTED)
 }
 
-/// Extract a JAR file to a directory, excluding specified paths
-fn extract_jar(jar_path: &PathBuf, dest_dir: &PathBuf, exclusions: &[&str]) -> Result<(), String> {
-    let file = File::open(jar_path)
-        .map_err(|e| format!("Failed to open JAR {}: {}", jar_path.display(), e))?;
-    
-    let mut archive = ZipArchive::new(file)
-        .map_err(|e| format!("Failed to read JAR {}: {}", jar_path.display(), e))?;
-    
-    for i in 0..archive.len() {
-        let mut entry = archive.by_index(i)
-            .map_err(|e| format!("Failed to read entry: {}", e))?;
-        
-        let name = entry.name().to_string();
-        
-        // Check exclusions
-        let excluded = exclusions.iter().any(|ex| name.starts_with(ex));
-        if excluded {
-            continue;
-        }
-        
-        // Skip directories and META-INF
-        if entry.is_dir() || name.starts_with("META-INF") {
+/// Replace the access token wherever it appears in the arguments
+fn redact_args(args: &[String], access_token: &str) -> Vec<String> {
+    args.iter().map(|a| redact(a, access_token)).collect()
+}
+
+/// Launch command broken into its parts, with the access token redacted
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LaunchPreview {
+    pub version_id: String,
+    pub java_path: String,
+    pub working_directory: String,
+    pub main_class: String,
+    pub classpath: Vec<String>,
+    pub jvm_args: Vec<String>,
+    pub game_args: Vec<String>,
+    /// Files the launch would still have to download
+    pub missing_files: Vec<String>,
+    pub wrapper: Vec<String>,
+    pub env_vars: Vec<(String, String)>,
+}
+
+pub fn preview_from_plan(plan: &LaunchPlan, access_token: &str) -> LaunchPreview {
+    LaunchPreview {
+        version_id: plan.version_id.clone(),
+        java_path: plan.java_path.to_string_lossy().to_string(),
+        working_directory: plan.game_dir.to_string_lossy().to_string(),
+        main_class: plan.main_class.clone(),
+        classpath: plan.classpath.clone(),
+        jvm_args: redact_args(&plan.jvm_args, access_token),
+        game_args: redact_args(&plan.game_args, access_token),
+        missing_files: plan.missing_files.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+        wrapper: redact_args(&plan.wrapper, access_token),
+        env_vars: plan.env_vars.iter()
+            .map(|(k, v)| (k.clone(), redact(v, access_token)))
+            .collect(),
+    }
+}
+
+/// Quote a value for a POSIX shell
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Whether a POSIX shell can export a variable of this name
+fn is_shell_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Quote an argument, swapping the access token for an environment variable reference
+fn shell_quote_with_token(value: &str, access_token: &str, token_expr: &str) -> String {
+    if access_token.is_empty() || !value.contains(access_token) {
+        return shell_quote(value);
+    }
+    value.split(access_token)
+        .map(shell_quote)
+        .collect::<Vec<_>>()
+        .join(token_expr)
+}
+
+/// Write a standalone launch.sh that starts the game the same way the launcher would.
+/// The token is never written out: with a placeholder it's read from $MC_ACCESS_TOKEN,
+/// otherwise an offline token is used.
+pub fn export_launch_script(plan: &LaunchPlan, access_token: &str, token_placeholder: bool, path: &std::path::Path) -> Result<(), String> {
+    let token_expr = if token_placeholder { "\"${MC_ACCESS_TOKEN:?set MC_ACCESS_TOKEN}\"" } else { "0" };
+
+    let mut script = String::from("#!/bin/sh\n");
+    script.push_str(&format!("# Minecraft {} launch script exported by Palethea Launcher\n", plan.version_id));
+    if token_placeholder {
+        script.push_str("# Set MC_ACCESS_TOKEN to a valid access token before running\n");
+    }
+    script.push_str(&format!("cd {} || exit 1\n", shell_quote(&plan.game_dir.to_string_lossy())));
+    for (key, value) in &plan.env_vars {
+        if !is_shell_name(key) {
+            log::warn!("Leaving {} out of the launch script, it isn't a valid shell variable name", key);
             continue;
         }
-        
-        let dest_path = dest_dir.join(&name);
-        
-        // Create parent directories if needed
-        if let Some(parent) = dest_path.parent() {
-            let _ = fs::create_dir_all(parent);
-        }
-        
-        // Only extract if file doesn't exist (avoid re-extracting on each launch)
-        if !dest_path.exists() {
-            let mut contents = Vec::new();
-            entry.read_to_end(&mut contents)
-                .map_err(|e| format!("Failed to read {}: {}", name, e))?;
-            
-            let mut out_file = File::create(&dest_path)
-                .map_err(|e| format!("Failed to create {}: {}", dest_path.display(), e))?;
-            out_file.write_all(&contents)
-                .map_err(|e| format!("Failed to write {}: {}", dest_path.display(), e))?;
-        }
+        script.push_str(&format!("export {}={}\n", key, shell_quote_with_token(value, access_token, token_expr)));
     }
-    
+    script.push_str("exec");
+    for word in &plan.wrapper {
+        script.push(' ');
+        script.push_str(&shell_quote(word));
+    }
+    script.push(' ');
+    script.push_str(&shell_quote(&plan.java_path.to_string_lossy()));
+    for arg in plan.jvm_args.iter().chain(std::iter::once(&plan.main_class)).chain(plan.game_args.iter()) {
+        script.push_str(" \\\n  ");
+        script.push_str(&shell_quote_with_token(arg, access_token, token_expr));
+    }
+    script.push('\n');
+
+    fs::write(path, script).map_err(|e| format!("Failed to write launch script: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("Failed to make launch script executable: {}", e))?;
+    }
+
     Ok(())
 }
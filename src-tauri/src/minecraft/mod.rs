@@ -7,10 +7,21 @@ pub mod auth;
 pub mod modrinth;
 pub mod files;
 pub mod fabric;
+pub mod quilt;
 pub mod forge;
 pub mod java;
+pub mod jvm;
+pub mod natives;
+pub mod hooks;
+pub mod history;
 pub mod logger;
 pub mod ping;
+pub mod query;
+pub mod lan;
+pub mod backups;
+pub mod regions;
+pub mod pack_format;
+pub mod server_status;
 
 use std::sync::Mutex;
 static LAUNCHER_VERSION: Mutex<String> = Mutex::new(String::new());
@@ -11,7 +11,7 @@ use tauri::{AppHandle, Emitter};
 use futures::stream::{self, StreamExt};
 
 use crate::minecraft::downloader::DownloadProgress;
-use crate::minecraft::{instances, fabric, forge};
+use crate::minecraft::{instances, fabric, quilt, forge};
 
 const MODRINTH_API_BASE: &str = "https://api.modrinth.com/v2";
 fn get_user_agent() -> String {
@@ -605,6 +605,9 @@ pub async fn install_modpack(
     } else if let Some(neoforge) = index.dependencies.get("neoforge") {
         mod_loader = instances::ModLoader::NeoForge;
         loader_version = Some(neoforge.clone());
+    } else if let Some(quilt) = index.dependencies.get("quilt-loader") {
+        mod_loader = instances::ModLoader::Quilt;
+        loader_version = Some(quilt.clone());
     }
 
     let mut instance = instances::get_instance(instance_id)?;
@@ -639,7 +642,7 @@ pub async fn install_modpack(
                         total_bytes: None,
                         downloaded_bytes: None,
                     });
-                    if let Err(e) = forge::install_forge(&instance, loader_ver).await {
+                    if let Err(e) = forge::install_forge(&instance, loader_ver, Some(app_handle)).await {
                         crate::log_error!(app_handle, "Failed to install Forge loader: {}", e);
                     }
                 },
@@ -652,10 +655,23 @@ pub async fn install_modpack(
                         total_bytes: None,
                         downloaded_bytes: None,
                     });
-                    if let Err(e) = forge::install_neoforge(&instance, loader_ver).await {
+                    if let Err(e) = forge::install_neoforge(&instance, loader_ver, Some(app_handle)).await {
                         crate::log_error!(app_handle, "Failed to install NeoForge loader: {}", e);
                     }
                 },
+                instances::ModLoader::Quilt => {
+                    let _ = app_handle.emit("download-progress", DownloadProgress { 
+                        stage: "Installing Quilt Loader...".to_string(), 
+                        percentage: 25.0,
+                        current: 15,
+                        total: 100,
+                        total_bytes: None,
+                        downloaded_bytes: None,
+                    });
+                    if let Err(e) = quilt::install_quilt(&instance, loader_ver).await {
+                        crate::log_error!(app_handle, "Failed to install Quilt loader: {}", e);
+                    }
+                },
                 _ => {}
             }
         }
@@ -0,0 +1,349 @@
+use crate::minecraft::downloader::{get_libraries_dir, get_minecraft_dir};
+use crate::minecraft::settings;
+use crate::minecraft::versions::{self, should_use_library, Artifact, Library, LibraryDownloads, VersionDetails};
+use sha1::{Digest, Sha1};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use uuid::Uuid;
+use zip::ZipArchive;
+
+/// LWJGL 3 has published linux-arm64 natives on Maven Central since 3.2.1
+const DEFAULT_LWJGL3_REPOSITORY: &str = "https://repo1.maven.org/maven2/";
+const ARM64_CLASSIFIER: &str = "natives-linux-arm64";
+/// Written once every native jar of a cache entry has been extracted
+const EXTRACTED_MARKER: &str = ".extracted";
+/// Ends the name of a folder natives are being extracted into
+const TEMP_SUFFIX: &str = ".tmp";
+/// Temporary folders older than this were left behind by a launch that died mid-extraction
+const STALE_TEMP_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// LWJGL versions without ARM64 builds and the closest release that has them.
+/// Natives must match the Java side, so the whole LWJGL set moves to the replacement.
+const LWJGL_ARM64_VERSIONS: &[(&str, &str)] = &[
+    ("3.1.2", "3.2.1"),
+    ("3.1.6", "3.2.1"),
+    ("3.2.1", "3.2.1"),
+    ("3.2.2", "3.2.2"),
+];
+
+/// Whether this machine needs ARM64 natives that old version JSONs don't ship
+pub fn needs_arm64_override() -> bool {
+    versions::get_os_name() == "linux" && versions::get_os_arch() == "arm64"
+}
+
+fn is_lwjgl(group: &str) -> bool {
+    group == "org.lwjgl" || group == "org.lwjgl.lwjgl"
+}
+
+fn replacement_version(version: &str) -> String {
+    LWJGL_ARM64_VERSIONS.iter()
+        .find(|(from, _)| *from == version)
+        .map(|(_, to)| to.to_string())
+        .unwrap_or_else(|| version.to_string())
+}
+
+/// Repository to pull ARM64 LWJGL from. There's no official LWJGL 2 build, so
+/// those only get swapped when a repository is configured in the settings.
+fn repository_for(version: &str, configured: Option<&str>) -> Option<String> {
+    match configured {
+        Some(repo) => Some(repo.to_string()),
+        None if version.starts_with("3.") => Some(DEFAULT_LWJGL3_REPOSITORY.to_string()),
+        None => None,
+    }
+}
+
+fn repository_artifact(repository: &str, name: &str) -> Artifact {
+    let path = versions::library_name_to_path(name);
+    let url = if repository.ends_with('/') {
+        format!("{}{}", repository, path)
+    } else {
+        format!("{}/{}", repository, path)
+    };
+    // Substituted artifacts have no known hash, they're downloaded once and kept
+    Artifact { path, sha1: String::new(), size: 0, url }
+}
+
+/// Swap one LWJGL library for its ARM64 equivalent, returns None if it's left as is
+fn override_library(library: &Library, configured_repository: Option<&str>) -> Option<Library> {
+    let parts: Vec<&str> = library.name.split(':').collect();
+    if parts.len() < 3 || !is_lwjgl(parts[0]) {
+        return None;
+    }
+    let (group, artifact, version) = (parts[0], parts[1], parts[2]);
+    let classifier = parts.get(3).copied();
+
+    // Already ARM64 (newer JSONs, or an earlier pass)
+    if classifier.map_or(false, |c| c.ends_with("arm64")) {
+        return None;
+    }
+    if library.natives.as_ref().and_then(|n| n.get("linux")).map_or(false, |c| c.ends_with("arm64")) {
+        return None;
+    }
+
+    let new_version = replacement_version(version);
+    let Some(repository) = repository_for(&new_version, configured_repository) else {
+        log::warn!("No ARM64 build of {} available, set a natives repository in the settings", library.name);
+        return None;
+    };
+
+    let mut replaced = library.clone();
+    match classifier {
+        // 1.19+ list the natives as separate classpath libraries
+        Some(c) if c.starts_with("natives-linux") => {
+            replaced.name = format!("{}:{}:{}:{}", group, artifact, new_version, ARM64_CLASSIFIER);
+        }
+        Some(_) => return None,
+        None => {
+            replaced.name = format!("{}:{}:{}", group, artifact, new_version);
+        }
+    }
+
+    let mut downloads = LibraryDownloads {
+        artifact: None,
+        classifiers: None,
+    };
+    let has_artifact = library.downloads.as_ref().map_or(true, |d| d.artifact.is_some());
+    if has_artifact && (new_version != version || classifier.is_some()) {
+        downloads.artifact = Some(repository_artifact(&repository, &replaced.name));
+    } else {
+        downloads.artifact = library.downloads.as_ref().and_then(|d| d.artifact.clone());
+    }
+
+    // 1.18 and older extract natives from a classifier jar
+    if library.natives.as_ref().map_or(false, |n| n.contains_key("linux")) {
+        let native_name = format!("{}:{}:{}:{}", group, artifact, new_version, ARM64_CLASSIFIER);
+        let mut classifiers = library.downloads.as_ref()
+            .and_then(|d| d.classifiers.clone())
+            .unwrap_or_default();
+        classifiers.insert(ARM64_CLASSIFIER.to_string(), repository_artifact(&repository, &native_name));
+        downloads.classifiers = Some(classifiers);
+
+        let mut natives = library.natives.clone().unwrap_or_default();
+        natives.insert("linux".to_string(), ARM64_CLASSIFIER.to_string());
+        replaced.natives = Some(natives);
+    } else {
+        downloads.classifiers = library.downloads.as_ref().and_then(|d| d.classifiers.clone());
+    }
+
+    replaced.downloads = Some(downloads);
+    Some(replaced)
+}
+
+/// Replace LWJGL libraries with ARM64 builds when running on Linux ARM64.
+/// Leaves the version untouched everywhere else.
+pub fn apply_overrides(version_details: &VersionDetails) -> VersionDetails {
+    let mut details = version_details.clone();
+    if !needs_arm64_override() {
+        return details;
+    }
+
+    let configured_repository = settings::load_settings().natives_repository.filter(|r| !r.trim().is_empty());
+    for library in details.libraries.iter_mut() {
+        if let Some(replaced) = override_library(library, configured_repository.as_deref()) {
+            log::info!("Using ARM64 natives {} in place of {}", replaced.name, library.name);
+            *library = replaced;
+        }
+    }
+    details
+}
+
+/// Shared cache of extracted natives, natives/<version>/<os>-<arch>-<hash>
+pub fn get_natives_cache_dir() -> PathBuf {
+    get_minecraft_dir().join("natives")
+}
+
+struct NativeJar {
+    path: PathBuf,
+    sha1: String,
+    exclusions: Vec<String>,
+}
+
+/// Native classifier jars this version extracts on the current OS
+fn collect_native_jars(version_details: &VersionDetails) -> Vec<NativeJar> {
+    let libraries_dir = get_libraries_dir();
+
+    version_details.libraries.iter()
+        .filter(|library| should_use_library(library))
+        .filter_map(|library| {
+            let classifier_key = versions::native_classifier(library)?;
+            let artifact = library.downloads.as_ref()?.classifiers.as_ref()?.get(&classifier_key)?;
+            Some(NativeJar {
+                path: libraries_dir.join(&artifact.path),
+                sha1: artifact.sha1.clone(),
+                exclusions: library.extract.as_ref()
+                    .and_then(|e| e.exclude.clone())
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Hash of the native artifacts, changes whenever a jar or its exclusions change
+fn cache_key(jars: &[NativeJar]) -> String {
+    let mut entries: Vec<String> = jars.iter()
+        .map(|jar| format!("{}|{}|{}", jar.path.display(), jar.sha1, jar.exclusions.join(",")))
+        .collect();
+    entries.sort();
+
+    let mut hasher = Sha1::new();
+    for entry in entries {
+        hasher.update(entry.as_bytes());
+        hasher.update(b"\n");
+    }
+    let hash = format!("{:x}", hasher.finalize());
+    format!("{}-{}-{}", versions::get_os_name(), versions::get_os_arch(), &hash[..16])
+}
+
+/// Cache folder the version's natives are extracted into, without extracting anything
+pub fn planned_natives_dir(version_details: &VersionDetails) -> PathBuf {
+    let version_details = apply_overrides(version_details);
+    let jars = collect_native_jars(&version_details);
+    get_natives_cache_dir().join(version_details.client_jar_id()).join(cache_key(&jars))
+}
+
+/// Extract the version's natives into the shared cache, skipping it entirely when the
+/// marker is already there. Returns the directory to use as java.library.path.
+pub fn prepare_natives(version_details: &VersionDetails) -> Result<PathBuf, String> {
+    let version_details = apply_overrides(version_details);
+    let jars = collect_native_jars(&version_details);
+
+    // Owned by the game version so the cache goes away with it
+    let version_cache = get_natives_cache_dir().join(version_details.client_jar_id());
+    let key = cache_key(&jars);
+    let natives_dir = version_cache.join(&key);
+    if natives_dir.join(EXTRACTED_MARKER).exists() {
+        return Ok(natives_dir);
+    }
+
+    // Extract into a folder of our own so an interrupted launch never leaves a half cache behind
+    // and concurrent launches of the same version don't touch each other's files
+    let temp_dir = version_cache.join(format!(".{}-{}{}", key, Uuid::new_v4(), TEMP_SUFFIX));
+    fs::create_dir_all(&temp_dir)
+        .map_err(|e| format!("Failed to create natives directory: {}", e))?;
+
+    let mut complete = true;
+    for jar in &jars {
+        if !jar.path.exists() {
+            log::warn!("Native library {} is missing", jar.path.display());
+            complete = false;
+            continue;
+        }
+        let exclusions: Vec<&str> = jar.exclusions.iter().map(|s| s.as_str()).collect();
+        if let Err(e) = extract_jar(&jar.path, &temp_dir, &exclusions) {
+            let _ = fs::remove_dir_all(&temp_dir);
+            return Err(e);
+        }
+    }
+
+    // Missing jars mean the next launch should try again, this one runs from its own folder
+    if !complete {
+        return Ok(temp_dir);
+    }
+
+    fs::write(temp_dir.join(EXTRACTED_MARKER), "")
+        .map_err(|e| format!("Failed to write natives marker: {}", e))?;
+
+    // A folder without the marker is an unfinished cache from an older launcher, move it
+    // aside rather than deleting it since a running game may still have it loaded
+    if natives_dir.exists() && !natives_dir.join(EXTRACTED_MARKER).exists() {
+        let aside = version_cache.join(format!(".{}-{}{}", key, Uuid::new_v4(), TEMP_SUFFIX));
+        let _ = fs::rename(&natives_dir, &aside);
+    }
+
+    if let Err(e) = fs::rename(&temp_dir, &natives_dir) {
+        // Another launch of the same version got there first
+        if natives_dir.join(EXTRACTED_MARKER).exists() {
+            let _ = fs::remove_dir_all(&temp_dir);
+            return Ok(natives_dir);
+        }
+        log::warn!("Failed to move natives into place, using {}: {}", temp_dir.display(), e);
+        return Ok(temp_dir);
+    }
+
+    remove_stale_entries(&version_cache, &natives_dir);
+    Ok(natives_dir)
+}
+
+/// Drop older cache entries of the same version, their artifacts have since changed.
+/// Temporary folders are only removed once they're old enough to belong to a dead launch.
+fn remove_stale_entries(version_cache: &Path, keep: &Path) {
+    let Ok(entries) = fs::read_dir(version_cache) else { return };
+    let prefix = format!("{}-{}-", versions::get_os_name(), versions::get_os_arch());
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path == keep || !path.is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let stale = if name.ends_with(TEMP_SUFFIX) {
+            entry.metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > STALE_TEMP_AGE)
+        } else {
+            name.starts_with(&prefix)
+        };
+        if stale {
+            let _ = fs::remove_dir_all(&path);
+        }
+    }
+}
+
+/// Remove every cached natives folder of a version
+pub fn delete_natives_cache(version_id: &str) -> Result<(), String> {
+    let version_cache = get_natives_cache_dir().join(version_id);
+    if version_cache.exists() {
+        fs::remove_dir_all(&version_cache)
+            .map_err(|e| format!("Failed to delete natives cache: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Extract a JAR file to a directory, excluding specified paths
+fn extract_jar(jar_path: &Path, dest_dir: &Path, exclusions: &[&str]) -> Result<(), String> {
+    let file = File::open(jar_path)
+        .map_err(|e| format!("Failed to open JAR {}: {}", jar_path.display(), e))?;
+
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read JAR {}: {}", jar_path.display(), e))?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)
+            .map_err(|e| format!("Failed to read entry: {}", e))?;
+
+        let name = entry.name().to_string();
+
+        // Check exclusions
+        if exclusions.iter().any(|ex| name.starts_with(ex)) {
+            continue;
+        }
+
+        // Skip directories and META-INF
+        if entry.is_dir() || name.starts_with("META-INF") {
+            continue;
+        }
+
+        let Some(relative) = entry.enclosed_name() else { continue };
+        let dest_path = dest_dir.join(relative);
+
+        // Create parent directories if needed
+        if let Some(parent) = dest_path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)
+            .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+
+        let mut out_file = File::create(&dest_path)
+            .map_err(|e| format!("Failed to create {}: {}", dest_path.display(), e))?;
+        out_file.write_all(&contents)
+            .map_err(|e| format!("Failed to write {}: {}", dest_path.display(), e))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,326 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PackKind {
+    Data,
+    Resource,
+}
+
+/// One row of the format table: a range of releases sharing the same pack formats.
+struct FormatEntry {
+    first: &'static str,
+    last: &'static str,
+    /// DataVersion of `first`, used when the version string can't be parsed (snapshots)
+    data_version: i32,
+    data_pack: Option<i32>,
+    resource_pack: i32,
+}
+
+/// Release -> pack_format table. Keep newest last and extend it when Mojang bumps formats.
+const FORMAT_TABLE: &[FormatEntry] = &[
+    FormatEntry { first: "1.6.1", last: "1.8.9", data_version: 0, data_pack: None, resource_pack: 1 },
+    FormatEntry { first: "1.9", last: "1.10.2", data_version: 169, data_pack: None, resource_pack: 2 },
+    FormatEntry { first: "1.11", last: "1.12.2", data_version: 819, data_pack: None, resource_pack: 3 },
+    FormatEntry { first: "1.13", last: "1.14.4", data_version: 1519, data_pack: Some(4), resource_pack: 4 },
+    FormatEntry { first: "1.15", last: "1.16.1", data_version: 2225, data_pack: Some(5), resource_pack: 5 },
+    FormatEntry { first: "1.16.2", last: "1.16.5", data_version: 2578, data_pack: Some(6), resource_pack: 6 },
+    FormatEntry { first: "1.17", last: "1.17.1", data_version: 2724, data_pack: Some(7), resource_pack: 7 },
+    FormatEntry { first: "1.18", last: "1.18.1", data_version: 2860, data_pack: Some(8), resource_pack: 8 },
+    FormatEntry { first: "1.18.2", last: "1.18.2", data_version: 2975, data_pack: Some(9), resource_pack: 8 },
+    FormatEntry { first: "1.19", last: "1.19.2", data_version: 3105, data_pack: Some(10), resource_pack: 9 },
+    FormatEntry { first: "1.19.3", last: "1.19.3", data_version: 3218, data_pack: Some(10), resource_pack: 12 },
+    FormatEntry { first: "1.19.4", last: "1.19.4", data_version: 3337, data_pack: Some(12), resource_pack: 13 },
+    FormatEntry { first: "1.20", last: "1.20.1", data_version: 3463, data_pack: Some(15), resource_pack: 15 },
+    FormatEntry { first: "1.20.2", last: "1.20.2", data_version: 3578, data_pack: Some(18), resource_pack: 18 },
+    FormatEntry { first: "1.20.3", last: "1.20.4", data_version: 3698, data_pack: Some(26), resource_pack: 22 },
+    FormatEntry { first: "1.20.5", last: "1.20.6", data_version: 3837, data_pack: Some(41), resource_pack: 32 },
+    FormatEntry { first: "1.21", last: "1.21.1", data_version: 3953, data_pack: Some(48), resource_pack: 34 },
+    FormatEntry { first: "1.21.2", last: "1.21.3", data_version: 4080, data_pack: Some(57), resource_pack: 42 },
+    FormatEntry { first: "1.21.4", last: "1.21.4", data_version: 4189, data_pack: Some(61), resource_pack: 46 },
+    FormatEntry { first: "1.21.5", last: "1.21.5", data_version: 4325, data_pack: Some(71), resource_pack: 55 },
+    FormatEntry { first: "1.21.6", last: "1.21.6", data_version: 4435, data_pack: Some(80), resource_pack: 63 },
+    FormatEntry { first: "1.21.7", last: "1.21.8", data_version: 4438, data_pack: Some(81), resource_pack: 64 },
+    FormatEntry { first: "1.21.9", last: "1.21.10", data_version: 4554, data_pack: Some(88), resource_pack: 69 },
+];
+
+/// Declared format range of a pack and how it compares to the instance
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PackFormatInfo {
+    pub pack_format: Option<i32>,
+    pub min_format: Option<i32>,
+    pub max_format: Option<i32>,
+    /// Format the instance's Minecraft version expects, if known
+    pub expected_format: Option<i32>,
+    /// "compatible", "outdated", "too_new" or "unknown"
+    pub compatibility: String,
+}
+
+impl PackFormatInfo {
+    pub fn is_incompatible(&self) -> bool {
+        self.compatibility == "outdated" || self.compatibility == "too_new"
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PackMcmeta {
+    pack: Option<PackSection>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PackSection {
+    pack_format: Option<i32>,
+    supported_formats: Option<serde_json::Value>,
+    // 1.21.9+ replaces supported_formats with these (int or [major, minor])
+    min_format: Option<serde_json::Value>,
+    max_format: Option<serde_json::Value>,
+}
+
+fn parse_release(version: &str) -> Option<Vec<u32>> {
+    // Only plain releases like 1.20.4 are in the table
+    version.split('.').map(|p| p.parse().ok()).collect()
+}
+
+fn format_entry_for_version(mc_version: &str) -> Option<&'static FormatEntry> {
+    let version = parse_release(mc_version)?;
+    FORMAT_TABLE.iter().find(|entry| {
+        match (parse_release(entry.first), parse_release(entry.last)) {
+            (Some(first), Some(last)) => version >= first && version <= last,
+            _ => false,
+        }
+    })
+}
+
+fn format_entry_for_data_version(data_version: i32) -> Option<&'static FormatEntry> {
+    FORMAT_TABLE.iter().rev().find(|entry| entry.data_version > 0 && data_version >= entry.data_version)
+}
+
+/// Pack format expected by a Minecraft version. Falls back to the DataVersion
+/// (e.g. from a world's level.dat) for snapshots, which maps to the closest older release.
+pub fn expected_format(kind: PackKind, mc_version: &str, data_version: Option<i32>) -> Option<i32> {
+    let entry = format_entry_for_version(mc_version)
+        .or_else(|| data_version.and_then(format_entry_for_data_version))?;
+    match kind {
+        PackKind::Data => entry.data_pack,
+        PackKind::Resource => Some(entry.resource_pack),
+    }
+}
+
+/// Major format number from an int or a [major, minor] array
+fn format_number(value: &serde_json::Value) -> Option<i32> {
+    match value {
+        serde_json::Value::Number(n) => n.as_i64().map(|n| n as i32),
+        serde_json::Value::Array(arr) => arr.first().and_then(|v| v.as_i64()).map(|n| n as i32),
+        _ => None,
+    }
+}
+
+/// Declared (min, max) format range of a pack.mcmeta
+fn declared_range(pack: &PackSection) -> (Option<i32>, Option<i32>) {
+    if pack.min_format.is_some() || pack.max_format.is_some() {
+        let min = pack.min_format.as_ref().and_then(format_number).or(pack.pack_format);
+        let max = pack.max_format.as_ref().and_then(format_number).or(pack.pack_format);
+        return (min, max);
+    }
+
+    match &pack.supported_formats {
+        // 42
+        Some(serde_json::Value::Number(n)) => {
+            let v = n.as_i64().map(|n| n as i32);
+            (v, v)
+        }
+        // [34, 42]
+        Some(serde_json::Value::Array(arr)) if arr.len() == 2 => {
+            (arr[0].as_i64().map(|n| n as i32), arr[1].as_i64().map(|n| n as i32))
+        }
+        // { "min_inclusive": 34, "max_inclusive": 42 }
+        Some(serde_json::Value::Object(obj)) => (
+            obj.get("min_inclusive").and_then(|v| v.as_i64()).map(|n| n as i32),
+            obj.get("max_inclusive").and_then(|v| v.as_i64()).map(|n| n as i32),
+        ),
+        _ => (pack.pack_format, pack.pack_format),
+    }
+}
+
+/// Read pack.mcmeta from a pack directory or zip/jar archive
+fn read_pack_mcmeta(path: &Path) -> Option<String> {
+    if path.is_dir() {
+        return fs::read_to_string(path.join("pack.mcmeta")).ok();
+    }
+
+    let file = File::open(path).ok()?;
+    let mut archive = zip::ZipArchive::new(file).ok()?;
+    let mut entry = archive.by_name("pack.mcmeta").ok()?;
+    let mut content = String::new();
+    entry.read_to_string(&mut content).ok()?;
+    Some(content)
+}
+
+/// Check a pack against the format expected by the instance
+pub fn check_pack(path: &Path, kind: PackKind, mc_version: &str, data_version: Option<i32>) -> PackFormatInfo {
+    let expected = expected_format(kind, mc_version, data_version);
+
+    let pack = read_pack_mcmeta(path)
+        // Some packs start with a BOM
+        .and_then(|s| serde_json::from_str::<PackMcmeta>(s.trim_start_matches('\u{feff}')).ok())
+        .and_then(|m| m.pack);
+
+    let pack = match pack {
+        Some(p) => p,
+        None => {
+            return PackFormatInfo {
+                pack_format: None,
+                min_format: None,
+                max_format: None,
+                expected_format: expected,
+                compatibility: "unknown".to_string(),
+            };
+        }
+    };
+
+    let (min_format, max_format) = declared_range(&pack);
+
+    let compatibility = match (expected, min_format, max_format) {
+        (Some(exp), Some(min), _) if exp < min => "too_new",
+        (Some(exp), _, Some(max)) if exp > max => "outdated",
+        (Some(_), Some(_), Some(_)) => "compatible",
+        _ => "unknown",
+    };
+
+    PackFormatInfo {
+        pack_format: pack.pack_format,
+        min_format,
+        max_format,
+        expected_format: expected,
+        compatibility: compatibility.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    #[test]
+    fn expected_formats_at_table_boundaries() {
+        let cases: &[(PackKind, &str, Option<i32>)] = &[
+            (PackKind::Resource, "1.6.1", Some(1)),
+            (PackKind::Resource, "1.8.9", Some(1)),
+            (PackKind::Resource, "1.9", Some(2)),
+            (PackKind::Resource, "1.12.2", Some(3)),
+            (PackKind::Data, "1.12.2", None),
+            (PackKind::Data, "1.13", Some(4)),
+            (PackKind::Data, "1.14.4", Some(4)),
+            (PackKind::Data, "1.15", Some(5)),
+            (PackKind::Data, "1.16.1", Some(5)),
+            (PackKind::Data, "1.16.2", Some(6)),
+            (PackKind::Data, "1.18.2", Some(9)),
+            (PackKind::Resource, "1.18.2", Some(8)),
+            (PackKind::Resource, "1.19.3", Some(12)),
+            (PackKind::Data, "1.20.4", Some(26)),
+            (PackKind::Data, "1.20.5", Some(41)),
+            (PackKind::Resource, "1.20.6", Some(32)),
+            (PackKind::Data, "1.21.8", Some(81)),
+            (PackKind::Data, "1.21.9", Some(88)),
+            (PackKind::Resource, "1.21.10", Some(69)),
+        ];
+        for (kind, version, expected) in cases {
+            assert_eq!(expected_format(*kind, version, None), *expected, "{:?} {}", kind, version);
+        }
+    }
+
+    #[test]
+    fn unknown_versions_fall_back_to_data_version() {
+        let cases: &[(&str, Option<i32>, Option<i32>)] = &[
+            // Snapshots map to the closest older release
+            ("24w14a", Some(3900), Some(41)),
+            ("24w14a", Some(3837), Some(41)),
+            ("24w14a", Some(3836), Some(26)),
+            ("24w14a", None, None),
+            // Older than anything with a known DataVersion
+            ("13w41a", Some(100), None),
+            ("1.99", None, None),
+            ("1.5.2", None, None),
+        ];
+        for (version, data_version, expected) in cases {
+            assert_eq!(expected_format(PackKind::Data, version, *data_version), *expected, "{} {:?}", version, data_version);
+        }
+    }
+
+    fn section(pack: serde_json::Value) -> PackSection {
+        serde_json::from_value(pack).unwrap()
+    }
+
+    #[test]
+    fn declared_ranges() {
+        let cases: &[(serde_json::Value, (Option<i32>, Option<i32>))] = &[
+            (json!({ "pack_format": 15 }), (Some(15), Some(15))),
+            (json!({ "pack_format": 15, "supported_formats": 42 }), (Some(42), Some(42))),
+            (json!({ "pack_format": 15, "supported_formats": [34, 42] }), (Some(34), Some(42))),
+            (json!({ "pack_format": 15, "supported_formats": { "min_inclusive": 34, "max_inclusive": 42 } }), (Some(34), Some(42))),
+            // A list that isn't a pair is ignored
+            (json!({ "pack_format": 15, "supported_formats": [34] }), (Some(15), Some(15))),
+            // 1.21.9+ fields, int or [major, minor], win over supported_formats
+            (json!({ "min_format": 81, "max_format": [88, 0], "supported_formats": [1, 2] }), (Some(81), Some(88))),
+            (json!({ "pack_format": 80, "max_format": 88 }), (Some(80), Some(88))),
+            (json!({}), (None, None)),
+        ];
+        for (pack, expected) in cases {
+            assert_eq!(declared_range(&section(pack.clone())), *expected, "{}", pack);
+        }
+    }
+
+    fn pack_dir(mcmeta: Option<serde_json::Value>) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("palethea-pack-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        if let Some(mcmeta) = mcmeta {
+            fs::write(dir.join("pack.mcmeta"), mcmeta.to_string()).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn check_pack_compatibility() {
+        let cases: &[(Option<serde_json::Value>, PackKind, &str, &str)] = &[
+            (Some(json!({ "pack": { "pack_format": 15 } })), PackKind::Data, "1.20.1", "compatible"),
+            (Some(json!({ "pack": { "pack_format": 10 } })), PackKind::Data, "1.20.1", "outdated"),
+            (Some(json!({ "pack": { "pack_format": 48 } })), PackKind::Data, "1.20.1", "too_new"),
+            (Some(json!({ "pack": { "pack_format": 10, "supported_formats": [10, 20] } })), PackKind::Data, "1.20.1", "compatible"),
+            (Some(json!({ "pack": { "pack_format": 22, "supported_formats": { "min_inclusive": 18, "max_inclusive": 22 } } })), PackKind::Resource, "1.20.4", "compatible"),
+            (Some(json!({ "pack": { "pack_format": 34 } })), PackKind::Resource, "1.20.4", "too_new"),
+            // No pack.mcmeta or no pack section
+            (None, PackKind::Data, "1.20.1", "unknown"),
+            (Some(json!({ "filter": {} })), PackKind::Data, "1.20.1", "unknown"),
+            // Versions missing from the table are never flagged
+            (Some(json!({ "pack": { "pack_format": 1 } })), PackKind::Data, "1.99", "unknown"),
+            (Some(json!({ "pack": { "pack_format": 1 } })), PackKind::Data, "25w01a", "unknown"),
+            (Some(json!({ "pack": { "pack_format": 1 } })), PackKind::Data, "1.12.2", "unknown"),
+        ];
+        for (mcmeta, kind, version, compatibility) in cases {
+            let dir = pack_dir(mcmeta.clone());
+            let info = check_pack(&dir, *kind, version, None);
+            fs::remove_dir_all(&dir).unwrap();
+            assert_eq!(info.compatibility, *compatibility, "{:?} {:?} {}", mcmeta, kind, version);
+            assert_eq!(info.is_incompatible(), matches!(*compatibility, "outdated" | "too_new"));
+        }
+    }
+
+    #[test]
+    fn check_pack_reads_archives_with_a_bom() {
+        let path = std::env::temp_dir().join(format!("palethea-pack-{}.zip", uuid::Uuid::new_v4()));
+        {
+            let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+            zip.start_file("pack.mcmeta", zip::write::SimpleFileOptions::default()).unwrap();
+            std::io::Write::write_all(&mut zip, "\u{feff}{\"pack\":{\"pack_format\":48}}".as_bytes()).unwrap();
+            zip.finish().unwrap();
+        }
+        let info = check_pack(&path, PackKind::Data, "1.21", None);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(info.pack_format, Some(48));
+        assert_eq!(info.expected_format, Some(48));
+        assert_eq!(info.compatibility, "compatible");
+    }
+}
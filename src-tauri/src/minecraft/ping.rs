@@ -1,11 +1,43 @@
 use tokio::net::TcpStream;
 use tokio::io::{AsyncWriteExt, AsyncReadExt};
-use std::time::Instant;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
 use serde::{Serialize, Deserialize};
 use trust_dns_resolver::TokioAsyncResolver;
 use trust_dns_resolver::config::*;
+use crate::minecraft::settings;
 
-#[derive(Debug, Serialize, Deserialize)]
+const DEFAULT_PORT: u16 = 25565;
+/// Protocol sent when the client version is unknown, servers answer with their own version
+const UNKNOWN_PROTOCOL: i32 = -1;
+/// Protocol byte sent in the 1.6 MC|PingHost extension of the legacy ping
+const LEGACY_PROTOCOL: u8 = 74;
+/// Pre-1.7 servers may sit silently on the modern handshake, give up on it in time for the legacy ping
+const STATUS_TIMEOUT: Duration = Duration::from_secs(3);
+/// The status is already in hand by then, a missing pong only costs the latency
+const PONG_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Release -> protocol version, used to ping with the instance's own protocol
+const PROTOCOL_VERSIONS: &[(&str, i32)] = &[
+    ("1.7.2", 4), ("1.7.4", 4), ("1.7.5", 4), ("1.7.6", 5), ("1.7.7", 5), ("1.7.8", 5), ("1.7.9", 5), ("1.7.10", 5),
+    ("1.8", 47), ("1.8.1", 47), ("1.8.2", 47), ("1.8.3", 47), ("1.8.4", 47), ("1.8.5", 47), ("1.8.6", 47), ("1.8.7", 47), ("1.8.8", 47), ("1.8.9", 47),
+    ("1.9", 107), ("1.9.1", 108), ("1.9.2", 109), ("1.9.3", 110), ("1.9.4", 110),
+    ("1.10", 210), ("1.10.1", 210), ("1.10.2", 210),
+    ("1.11", 315), ("1.11.1", 316), ("1.11.2", 316),
+    ("1.12", 335), ("1.12.1", 338), ("1.12.2", 340),
+    ("1.13", 393), ("1.13.1", 401), ("1.13.2", 404),
+    ("1.14", 477), ("1.14.1", 480), ("1.14.2", 485), ("1.14.3", 490), ("1.14.4", 498),
+    ("1.15", 573), ("1.15.1", 575), ("1.15.2", 578),
+    ("1.16", 735), ("1.16.1", 736), ("1.16.2", 751), ("1.16.3", 753), ("1.16.4", 754), ("1.16.5", 754),
+    ("1.17", 755), ("1.17.1", 756),
+    ("1.18", 757), ("1.18.1", 757), ("1.18.2", 758),
+    ("1.19", 759), ("1.19.1", 760), ("1.19.2", 760), ("1.19.3", 761), ("1.19.4", 762),
+    ("1.20", 763), ("1.20.1", 763), ("1.20.2", 764), ("1.20.3", 765), ("1.20.4", 765), ("1.20.5", 766), ("1.20.6", 766),
+    ("1.21", 767), ("1.21.1", 767), ("1.21.2", 768), ("1.21.3", 768), ("1.21.4", 769), ("1.21.5", 770),
+    ("1.21.6", 771), ("1.21.7", 772), ("1.21.8", 772), ("1.21.9", 773), ("1.21.10", 773),
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PingResponse {
     pub latency_ms: u64,
     pub version_name: String,
@@ -14,6 +46,9 @@ pub struct PingResponse {
     pub online_players: i32,
     pub motd: String,
     pub favicon: Option<String>,
+    /// True when the server only answered the pre-1.7 legacy ping
+    #[serde(default)]
+    pub legacy: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -36,7 +71,9 @@ struct StatusPlayers {
     online: i32,
 }
 
-async fn write_varint(buf: &mut Vec<u8>, mut val: i32) {
+async fn write_varint(buf: &mut Vec<u8>, val: i32) {
+    // Shift as unsigned so negative values (the -1 "unknown" protocol) end after five bytes
+    let mut val = val as u32;
     loop {
         let mut b = (val & 0x7F) as u8;
         val >>= 7;
@@ -91,21 +128,250 @@ fn parse_motd(description: &serde_json::Value) -> String {
     "".to_string()
 }
 
-pub async fn ping_server(address: &str) -> Result<PingResponse, String> {
+/// Protocol number of a Minecraft release, None for unknown or pre-1.7 versions
+pub fn protocol_for_version(mc_version: &str) -> Option<i32> {
+    PROTOCOL_VERSIONS.iter()
+        .find(|(v, _)| *v == mc_version)
+        .map(|(_, p)| *p)
+}
+
+/// Split an address into host and optional port.
+/// Accepts host, host:port, [ipv6]:port, [ipv6] and bare IPv6 literals.
+pub fn parse_address(address: &str) -> Result<(String, Option<u16>), String> {
     let address = address.trim();
-    let parts: Vec<&str> = address.split(':').collect();
-    let original_host = parts[0];
-    let mut host = original_host.to_string();
-    let mut port = if parts.len() > 1 {
-        parts[1].parse::<u16>().map_err(|_| "Invalid port")?
+    if address.is_empty() {
+        return Err("Empty server address".to_string());
+    }
+
+    let parse_port = |p: &str| p.parse::<u16>().map_err(|_| format!("Invalid port: {}", p));
+
+    if let Some(rest) = address.strip_prefix('[') {
+        let end = rest.find(']').ok_or("Missing ']' in IPv6 address")?;
+        let host = &rest[..end];
+        let after = &rest[end + 1..];
+        let port = match after.strip_prefix(':') {
+            Some(p) => Some(parse_port(p)?),
+            None if after.is_empty() => None,
+            None => return Err(format!("Invalid server address: {}", address)),
+        };
+        return Ok((host.to_string(), port));
+    }
+
+    // More than one colon without brackets can only be an IPv6 literal
+    if address.matches(':').count() > 1 {
+        return Ok((address.to_string(), None));
+    }
+
+    match address.split_once(':') {
+        Some((host, port)) => Ok((host.to_string(), Some(parse_port(port)?))),
+        None => Ok((address.to_string(), None)),
+    }
+}
+
+/// System resolver, or the DNS server configured in the launcher settings
+fn build_resolver() -> (TokioAsyncResolver, bool) {
+    let dns_override = settings::load_settings().dns_server
+        .and_then(|s| s.trim().parse::<IpAddr>().ok());
+
+    if let Some(ip) = dns_override {
+        let config = ResolverConfig::from_parts(None, vec![], NameServerConfigGroup::from_ips_clear(&[ip], 53, true));
+        return (TokioAsyncResolver::tokio(config, ResolverOpts::default()), true);
+    }
+
+    match TokioAsyncResolver::tokio_from_system_conf() {
+        Ok(resolver) => (resolver, false),
+        Err(e) => {
+            log::warn!("Failed to read system DNS config, using defaults: {}", e);
+            (TokioAsyncResolver::tokio(ResolverConfig::default(), ResolverOpts::default()), false)
+        }
+    }
+}
+
+async fn connect(resolver: &TokioAsyncResolver, use_resolver: bool, host: &str, port: u16) -> Result<TcpStream, String> {
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return TcpStream::connect(SocketAddr::new(ip, port)).await
+            .map_err(|e| format!("Connection failed: {}", e));
+    }
+
+    // With a DNS override the A/AAAA lookup has to go through it as well
+    if use_resolver {
+        let ips = resolver.lookup_ip(host).await
+            .map_err(|e| format!("Failed to resolve {}: {}", host, e))?;
+        let mut last_error = format!("No addresses found for {}", host);
+        for ip in ips.iter() {
+            match TcpStream::connect(SocketAddr::new(ip, port)).await {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = format!("Connection failed: {}", e),
+            }
+        }
+        return Err(last_error);
+    }
+
+    TcpStream::connect((host, port)).await
+        .map_err(|e| format!("Connection failed: {}", e))
+}
+
+async fn write_packet(stream: &mut TcpStream, payload: &[u8]) -> tokio::io::Result<()> {
+    let mut packet = Vec::new();
+    write_varint(&mut packet, payload.len() as i32).await;
+    packet.extend_from_slice(payload);
+    stream.write_all(&packet).await
+}
+
+/// Post-1.7 status exchange: handshake, status request, then ping/pong for latency
+async fn modern_ping(stream: &mut TcpStream, hostname: &str, port: u16, protocol: i32, connect_latency: u64) -> Result<PingResponse, String> {
+    let response = tokio::time::timeout(STATUS_TIMEOUT, read_status(stream, hostname, port, protocol))
+        .await
+        .map_err(|_| "No status response".to_string())??;
+
+    // Ping/pong round trip. Some proxies close right after the status or never answer, so fall back to connect time.
+    let latency_ms = match tokio::time::timeout(PONG_TIMEOUT, measure_pong(stream)).await {
+        Ok(Ok(ms)) => ms,
+        Ok(Err(e)) => {
+            log::debug!("Pong failed, using connection latency: {}", e);
+            connect_latency
+        }
+        Err(_) => {
+            log::debug!("Pong timed out, using connection latency");
+            connect_latency
+        }
+    };
+
+    Ok(PingResponse {
+        latency_ms,
+        version_name: response.version.name,
+        protocol_version: response.version.protocol,
+        max_players: response.players.max,
+        online_players: response.players.online,
+        motd: parse_motd(&response.description),
+        favicon: response.favicon,
+        legacy: false,
+    })
+}
+
+/// Handshake and status request, returning the server's status JSON
+async fn read_status(stream: &mut TcpStream, hostname: &str, port: u16, protocol: i32) -> Result<StatusResponse, String> {
+    // 1. Handshake
+    let mut handshake = Vec::new();
+    write_varint(&mut handshake, 0x00).await; // Packet ID
+    write_varint(&mut handshake, protocol).await;
+    write_string(&mut handshake, hostname).await;
+    handshake.extend_from_slice(&port.to_be_bytes());
+    write_varint(&mut handshake, 1).await; // Next state (1 = status)
+    write_packet(stream, &handshake).await.map_err(|e| format!("Handshake write failed: {}", e))?;
+
+    // 2. Status Request
+    write_packet(stream, &[0x00]).await.map_err(|e| format!("Status request failed: {}", e))?;
+
+    // 3. Read Status Response
+    let _len = read_varint(stream).await.map_err(|e| format!("Read length failed: {}", e))?;
+    let id = read_varint(stream).await.map_err(|e| format!("Read ID failed: {}", e))?;
+    if id != 0x00 {
+        return Err(format!("Unexpected packet ID: {}", id));
+    }
+
+    let json_len = read_varint(stream).await.map_err(|e| format!("Read JSON length failed: {}", e))? as usize;
+    let mut json_bytes = vec![0u8; json_len];
+    stream.read_exact(&mut json_bytes).await.map_err(|e| format!("Read JSON data failed: {}", e))?;
+
+    serde_json::from_slice(&json_bytes).map_err(|e| format!("JSON decode failed: {}", e))
+}
+
+async fn measure_pong(stream: &mut TcpStream) -> Result<u64, String> {
+    let payload = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+
+    let mut ping = vec![0x01];
+    ping.extend_from_slice(&payload.to_be_bytes());
+
+    let start = Instant::now();
+    write_packet(stream, &ping).await.map_err(|e| e.to_string())?;
+
+    let _len = read_varint(stream).await.map_err(|e| e.to_string())?;
+    let id = read_varint(stream).await.map_err(|e| e.to_string())?;
+    if id != 0x01 {
+        return Err(format!("Unexpected packet ID: {}", id));
+    }
+    let echoed = stream.read_i64().await.map_err(|e| e.to_string())?;
+    if echoed != payload {
+        return Err("Pong payload mismatch".to_string());
+    }
+
+    Ok(start.elapsed().as_millis() as u64)
+}
+
+fn utf16_be(s: &str) -> Vec<u8> {
+    s.encode_utf16().flat_map(|c| c.to_be_bytes()).collect()
+}
+
+/// Pre-1.7 server list ping (0xFE 0x01 with the 1.6 MC|PingHost extension)
+async fn legacy_ping(stream: &mut TcpStream, hostname: &str, port: u16) -> Result<PingResponse, String> {
+    let start = Instant::now();
+
+    let host_bytes = utf16_be(hostname);
+    let channel = "MC|PingHost";
+    let mut request = vec![0xFE, 0x01, 0xFA];
+    request.extend_from_slice(&(channel.len() as u16).to_be_bytes());
+    request.extend_from_slice(&utf16_be(channel));
+    request.extend_from_slice(&((7 + host_bytes.len()) as u16).to_be_bytes());
+    request.push(LEGACY_PROTOCOL);
+    request.extend_from_slice(&((host_bytes.len() / 2) as u16).to_be_bytes());
+    request.extend_from_slice(&host_bytes);
+    request.extend_from_slice(&(port as i32).to_be_bytes());
+    stream.write_all(&request).await.map_err(|e| format!("Legacy ping write failed: {}", e))?;
+
+    let id = stream.read_u8().await.map_err(|e| format!("Legacy ping read failed: {}", e))?;
+    if id != 0xFF {
+        return Err(format!("Unexpected legacy packet ID: {:#x}", id));
+    }
+    let latency_ms = start.elapsed().as_millis() as u64;
+
+    let chars = stream.read_u16().await.map_err(|e| format!("Legacy ping read failed: {}", e))? as usize;
+    let mut raw = vec![0u8; chars * 2];
+    stream.read_exact(&mut raw).await.map_err(|e| format!("Legacy ping read failed: {}", e))?;
+    let units: Vec<u16> = raw.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+    let text = String::from_utf16_lossy(&units);
+
+    // 1.4+: "§1\0protocol\0version\0motd\0online\0max", older: "motd§online§max"
+    let (protocol_version, version_name, motd, online, max) = if let Some(rest) = text.strip_prefix("\u{a7}1\0") {
+        let fields: Vec<&str> = rest.split('\0').collect();
+        if fields.len() < 5 {
+            return Err("Malformed legacy ping response".to_string());
+        }
+        (fields[0].parse().unwrap_or(0), fields[1].to_string(), fields[2].to_string(), fields[3], fields[4])
     } else {
-        25565
+        let fields: Vec<&str> = text.rsplitn(3, '\u{a7}').collect();
+        if fields.len() < 3 {
+            return Err("Malformed legacy ping response".to_string());
+        }
+        (0, "Legacy".to_string(), fields[2].to_string(), fields[1], fields[0])
     };
 
-    // Try SRV lookup with a more robust configuration
-    if parts.len() == 1 {
-        // Use Cloudflare as a reliable DNS provider
-        let resolver = TokioAsyncResolver::tokio(ResolverConfig::cloudflare(), ResolverOpts::default());
+    Ok(PingResponse {
+        latency_ms,
+        version_name,
+        protocol_version,
+        max_players: max.parse().unwrap_or(0),
+        online_players: online.parse().unwrap_or(0),
+        motd,
+        favicon: None,
+        legacy: true,
+    })
+}
+
+/// Ping a server with the given client protocol, falling back to the legacy ping
+/// for servers that don't understand the modern handshake.
+pub async fn ping_server(address: &str, protocol: Option<i32>) -> Result<PingResponse, String> {
+    let (original_host, explicit_port) = parse_address(address)?;
+    let mut host = original_host.clone();
+    let mut port = explicit_port.unwrap_or(DEFAULT_PORT);
+
+    let (resolver, use_resolver) = build_resolver();
+
+    // SRV records only apply when no port was given and the host isn't an IP
+    if explicit_port.is_none() && original_host.parse::<IpAddr>().is_err() {
         let srv_query = format!("_minecraft._tcp.{}", original_host);
         if let Ok(srv_lookup) = resolver.srv_lookup(srv_query).await {
             if let Some(srv) = srv_lookup.iter().next() {
@@ -115,65 +381,131 @@ pub async fn ping_server(address: &str) -> Result<PingResponse, String> {
         }
     }
 
-    let start = Instant::now();
-    
+    let protocol = protocol.unwrap_or(UNKNOWN_PROTOCOL);
+
     // Wrap connection and status exchange in a combined timeout
     let ping_future = async {
-        let mut stream = TcpStream::connect(format!("{}:{}", host, port)).await
-            .map_err(|e| format!("Connection failed: {}", e))?;
-        
-        // Measure real network latency as just the connection time
-        let network_latency = start.elapsed().as_millis() as u64;
-
-        // 1. Handshake
-        let mut handshake = Vec::new();
-        write_varint(&mut handshake, 0x00).await; // Packet ID
-        write_varint(&mut handshake, 47).await;   // Protocol Version (1.8.x)
-        write_string(&mut handshake, original_host).await;
-        handshake.extend_from_slice(&port.to_be_bytes());
-        write_varint(&mut handshake, 1).await; // Next state (1 = status)
-
-        let mut packet = Vec::new();
-        write_varint(&mut packet, handshake.len() as i32).await;
-        packet.extend_from_slice(&handshake);
-        stream.write_all(&packet).await.map_err(|e| format!("Handshake write failed: {}", e))?;
-
-        // 2. Status Request
-        let mut status_request = Vec::new();
-        write_varint(&mut status_request, 0x00).await; // Packet ID
-        
-        let mut packet = Vec::new();
-        write_varint(&mut packet, status_request.len() as i32).await;
-        packet.extend_from_slice(&status_request);
-        stream.write_all(&packet).await.map_err(|e| format!("Status request failed: {}", e))?;
-
-        // 3. Read Status Response
-        let _len = read_varint(&mut stream).await.map_err(|e| format!("Read length failed: {}", e))?;
-        let id = read_varint(&mut stream).await.map_err(|e| format!("Read ID failed: {}", e))?;
-        if id != 0x00 {
-            return Err(format!("Unexpected packet ID: {}", id));
-        }
+        let start = Instant::now();
+        let mut stream = connect(&resolver, use_resolver, &host, port).await?;
+        let connect_latency = start.elapsed().as_millis() as u64;
 
-        let json_len = read_varint(&mut stream).await.map_err(|e| format!("Read JSON length failed: {}", e))? as usize;
-        let mut json_bytes = vec![0u8; json_len];
-        stream.read_exact(&mut json_bytes).await.map_err(|e| format!("Read JSON data failed: {}", e))?;
-
-        let response: StatusResponse = serde_json::from_slice(&json_bytes).map_err(|e| format!("JSON decode failed: {}", e))?;
-        
-        // Use the connection latency for the main display, as it's the most stable metric
-        Ok(PingResponse {
-            latency_ms: network_latency,
-            version_name: response.version.name,
-            protocol_version: response.version.protocol,
-            max_players: response.players.max,
-            online_players: response.players.online,
-            motd: parse_motd(&response.description),
-            favicon: response.favicon,
-        })
+        match modern_ping(&mut stream, &original_host, port, protocol, connect_latency).await {
+            Ok(response) => Ok(response),
+            Err(modern_err) => {
+                // Old servers drop the modern handshake, retry on a fresh connection
+                let mut stream = connect(&resolver, use_resolver, &host, port).await?;
+                legacy_ping(&mut stream, &original_host, port).await
+                    .map_err(|legacy_err| format!("{} (legacy ping: {})", modern_err, legacy_err))
+            }
+        }
     };
 
-    match tokio::time::timeout(std::time::Duration::from_secs(10), ping_future).await {
+    match tokio::time::timeout(Duration::from_secs(10), ping_future).await {
         Ok(res) => res,
         Err(_) => Err(format!("Ping timed out (tried {}:{})", host, port)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    const STATUS_JSON: &str = r#"{"version":{"name":"1.20.4","protocol":765},"players":{"max":20,"online":3},"description":{"text":"A ","extra":[{"text":"test server"}]}}"#;
+
+    async fn read_packet(stream: &mut TcpStream) -> Vec<u8> {
+        let len = read_varint(stream).await.unwrap() as usize;
+        let mut payload = vec![0u8; len];
+        stream.read_exact(&mut payload).await.unwrap();
+        payload
+    }
+
+    /// Answer the handshake and status request with STATUS_JSON
+    async fn serve_status(stream: &mut TcpStream) {
+        let handshake = read_packet(stream).await;
+        assert_eq!(handshake[0], 0x00);
+        assert_eq!(read_packet(stream).await, vec![0x00]);
+
+        let mut payload = Vec::new();
+        write_varint(&mut payload, 0x00).await;
+        write_string(&mut payload, STATUS_JSON).await;
+        write_packet(stream, &payload).await.unwrap();
+    }
+
+    async fn local_server() -> (TcpListener, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        (listener, address)
+    }
+
+    #[tokio::test]
+    async fn modern_status_with_pong() {
+        let (listener, address) = local_server().await;
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            serve_status(&mut stream).await;
+            let ping = read_packet(&mut stream).await;
+            assert_eq!(ping[0], 0x01);
+            write_packet(&mut stream, &ping).await.unwrap();
+        });
+
+        let response = ping_server(&address, Some(765)).await.unwrap();
+        assert!(!response.legacy);
+        assert_eq!(response.version_name, "1.20.4");
+        assert_eq!(response.protocol_version, 765);
+        assert_eq!(response.online_players, 3);
+        assert_eq!(response.max_players, 20);
+        assert_eq!(response.motd, "A test server");
+    }
+
+    #[tokio::test]
+    async fn legacy_kick_after_silent_handshake() {
+        let (listener, address) = local_server().await;
+        tokio::spawn(async move {
+            // A pre-1.7 server doesn't understand the modern handshake and never answers it
+            let (_silent, _) = listener.accept().await.unwrap();
+
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut magic = [0u8; 2];
+            stream.read_exact(&mut magic).await.unwrap();
+            assert_eq!(magic, [0xFE, 0x01]);
+
+            let text = ["\u{a7}1", "74", "1.6.4", "A Minecraft Server", "5", "20"].join("\0");
+            let units = utf16_be(&text);
+            let mut kick = vec![0xFF];
+            kick.extend_from_slice(&((units.len() / 2) as u16).to_be_bytes());
+            kick.extend_from_slice(&units);
+            stream.write_all(&kick).await.unwrap();
+        });
+
+        let response = ping_server(&address, None).await.unwrap();
+        assert!(response.legacy);
+        assert_eq!(response.protocol_version, 74);
+        assert_eq!(response.version_name, "1.6.4");
+        assert_eq!(response.motd, "A Minecraft Server");
+        assert_eq!(response.online_players, 5);
+        assert_eq!(response.max_players, 20);
+    }
+
+    #[tokio::test]
+    async fn status_kept_when_pong_never_comes() {
+        let (listener, address) = local_server().await;
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            serve_status(&mut stream).await;
+            let _ = read_packet(&mut stream).await;
+            // Hold the connection open without answering the ping
+            let _ = done_rx.await;
+        });
+
+        let start = Instant::now();
+        let response = ping_server(&address, Some(765)).await.unwrap();
+        let _ = done_tx.send(());
+
+        assert!(start.elapsed() < PONG_TIMEOUT + Duration::from_secs(1));
+        assert!(!response.legacy);
+        assert_eq!(response.version_name, "1.20.4");
+        assert_eq!(response.motd, "A test server");
+    }
+}
@@ -0,0 +1,160 @@
+use crate::minecraft::ping::parse_address;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+const DEFAULT_PORT: u16 = 25565;
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const MAGIC: [u8; 2] = [0xFE, 0xFD];
+const TYPE_HANDSHAKE: u8 = 0x09;
+const TYPE_STAT: u8 = 0x00;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueryResponse {
+    pub motd: String,
+    pub game_type: String,
+    pub game_id: String,
+    pub version: String,
+    /// Server software, e.g. "Paper on Bukkit 1.20.4-R0.1-SNAPSHOT"
+    pub software: Option<String>,
+    pub plugins: Vec<String>,
+    pub map: String,
+    pub online_players: i32,
+    pub max_players: i32,
+    pub host_port: Option<u16>,
+    pub host_ip: Option<String>,
+    pub players: Vec<String>,
+}
+
+/// Query strings are ISO-8859-1
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+/// Split a buffer into null-terminated strings
+fn read_cstring<'a>(buf: &'a [u8], pos: &mut usize) -> Option<&'a [u8]> {
+    let start = *pos;
+    let end = start + buf.get(start..)?.iter().position(|&b| b == 0)?;
+    *pos = end + 1;
+    Some(&buf[start..end])
+}
+
+fn build_request(packet_type: u8, session_id: i32, payload: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(7 + payload.len());
+    packet.extend_from_slice(&MAGIC);
+    packet.push(packet_type);
+    packet.extend_from_slice(&session_id.to_be_bytes());
+    packet.extend_from_slice(payload);
+    packet
+}
+
+async fn exchange(socket: &UdpSocket, request: &[u8], expected_type: u8, session_id: i32) -> Result<Vec<u8>, String> {
+    socket.send(request).await.map_err(|e| format!("Query send failed: {}", e))?;
+
+    let mut buf = vec![0u8; 65535];
+    let len = tokio::time::timeout(QUERY_TIMEOUT, socket.recv(&mut buf)).await
+        .map_err(|_| "Query timed out, is enable-query=true set on the server?".to_string())?
+        .map_err(|e| format!("Query receive failed: {}", e))?;
+    buf.truncate(len);
+
+    if buf.len() < 5 || buf[0] != expected_type {
+        return Err("Unexpected query response".to_string());
+    }
+    if i32::from_be_bytes([buf[1], buf[2], buf[3], buf[4]]) != session_id {
+        return Err("Query session mismatch".to_string());
+    }
+
+    Ok(buf.split_off(5))
+}
+
+/// "Paper on Bukkit 1.20.4: WorldEdit 7.2; LuckPerms 5.4" -> (software, plugins)
+fn parse_plugins(raw: &str) -> (Option<String>, Vec<String>) {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return (None, Vec::new());
+    }
+
+    match raw.split_once(':') {
+        Some((software, list)) => {
+            let plugins = list.split(';')
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty())
+                .collect();
+            (Some(software.trim().to_string()), plugins)
+        }
+        None => (Some(raw.to_string()), Vec::new()),
+    }
+}
+
+/// Full stat request using the GameSpy4 query protocol (needs enable-query=true)
+pub async fn query_server(address: &str, query_port: Option<u16>) -> Result<QueryResponse, String> {
+    let (host, port) = parse_address(address)?;
+    let port = query_port.or(port).unwrap_or(DEFAULT_PORT);
+
+    let target = tokio::net::lookup_host((host.as_str(), port)).await
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("No addresses found for {}", host))?;
+
+    let bind_addr = if target.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" };
+    let socket = UdpSocket::bind(bind_addr).await.map_err(|e| format!("Failed to open UDP socket: {}", e))?;
+    socket.connect(target).await.map_err(|e| format!("Failed to connect UDP socket: {}", e))?;
+
+    // Only the lower 4 bits of each byte are used by the server
+    let session_id = (std::process::id() as i32) & 0x0F0F_0F0F;
+
+    // 1. Handshake -> challenge token as a null-terminated decimal string
+    let handshake = exchange(&socket, &build_request(TYPE_HANDSHAKE, session_id, &[]), TYPE_HANDSHAKE, session_id).await?;
+    let mut pos = 0;
+    let token: i32 = read_cstring(&handshake, &mut pos)
+        .map(latin1)
+        .and_then(|s| s.trim().parse().ok())
+        .ok_or("Invalid challenge token")?;
+
+    // 2. Full stat: challenge token followed by 4 bytes of padding
+    let mut payload = token.to_be_bytes().to_vec();
+    payload.extend_from_slice(&[0, 0, 0, 0]);
+    let stat = exchange(&socket, &build_request(TYPE_STAT, session_id, &payload), TYPE_STAT, session_id).await?;
+
+    // 11 bytes of constant padding ("splitnum\0\x80\0") before the key/value section
+    let mut pos = 11;
+    let mut values: HashMap<String, String> = HashMap::new();
+    loop {
+        let key = read_cstring(&stat, &mut pos).ok_or("Malformed query response")?;
+        if key.is_empty() {
+            break;
+        }
+        let value = read_cstring(&stat, &mut pos).ok_or("Malformed query response")?;
+        values.insert(latin1(key), latin1(value));
+    }
+
+    // 10 bytes of padding ("\x01player_\0\0") before the player list
+    pos += 10;
+    let mut players = Vec::new();
+    while let Some(name) = read_cstring(&stat, &mut pos) {
+        if name.is_empty() {
+            break;
+        }
+        players.push(latin1(name));
+    }
+
+    let get = |key: &str| values.get(key).cloned().unwrap_or_default();
+    let (software, plugins) = parse_plugins(&get("plugins"));
+
+    Ok(QueryResponse {
+        motd: get("hostname"),
+        game_type: get("gametype"),
+        game_id: get("game_id"),
+        version: get("version"),
+        software,
+        plugins,
+        map: get("map"),
+        online_players: get("numplayers").parse().unwrap_or(0),
+        max_players: get("maxplayers").parse().unwrap_or(0),
+        host_port: values.get("hostport").and_then(|p| p.parse().ok()),
+        host_ip: values.get("hostip").cloned(),
+        players,
+    })
+}
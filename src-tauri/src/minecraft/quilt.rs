@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+
+use crate::minecraft::downloader::get_libraries_dir;
+use crate::minecraft::fabric::{download_library_with_sha1, FabricLibraries, FabricLibrary, FabricMainClass};
+use crate::minecraft::instances::Instance;
+
+const QUILT_META_API: &str = "https://meta.quiltmc.org/v3";
+const QUILT_MAVEN: &str = "https://maven.quiltmc.org/repository/release/";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuiltLoaderVersion {
+    pub loader: QuiltLoader,
+    /// Quilt's hashed mappings for the game version
+    pub hashed: QuiltMappings,
+    pub intermediary: QuiltMappings,
+    #[serde(rename = "launcherMeta")]
+    pub launcher_meta: QuiltLauncherMeta,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuiltLoader {
+    pub separator: String,
+    pub build: u32,
+    pub maven: String,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuiltMappings {
+    pub maven: String,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuiltLauncherMeta {
+    pub version: u32,
+    pub libraries: FabricLibraries,
+    #[serde(rename = "mainClass")]
+    pub main_class: FabricMainClass,
+}
+
+/// Launcher profile JSON from Quilt meta, lists every library needed at runtime
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuiltProfile {
+    pub id: String,
+    #[serde(rename = "inheritsFrom")]
+    pub inherits_from: String,
+    #[serde(rename = "mainClass")]
+    pub main_class: String,
+    #[serde(default)]
+    pub arguments: Option<serde_json::Value>,
+    pub libraries: Vec<FabricLibrary>,
+}
+
+/// What gets saved to quilt.json in the instance folder
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuiltInstallation {
+    pub loader: QuiltLoaderVersion,
+    pub profile: QuiltProfile,
+}
+
+async fn fetch_json<T: serde::de::DeserializeOwned>(url: &str) -> Result<T, Box<dyn Error + Send + Sync>> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(url)
+        .header("User-Agent", format!("PaletheaLauncher/{}", super::get_launcher_version()))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch Quilt info: {}", response.status()).into());
+    }
+
+    Ok(response.json().await?)
+}
+
+/// Fetch Quilt loader info for a game version and loader version
+pub async fn get_quilt_loader_info(
+    game_version: &str,
+    loader_version: &str,
+) -> Result<QuiltLoaderVersion, Box<dyn Error + Send + Sync>> {
+    fetch_json(&format!("{}/versions/loader/{}/{}", QUILT_META_API, game_version, loader_version)).await
+}
+
+/// Fetch the launcher profile JSON for a game version and loader version
+pub async fn get_quilt_profile(
+    game_version: &str,
+    loader_version: &str,
+) -> Result<QuiltProfile, Box<dyn Error + Send + Sync>> {
+    fetch_json(&format!("{}/versions/loader/{}/{}/profile/json", QUILT_META_API, game_version, loader_version)).await
+}
+
+/// Install Quilt for an instance
+pub async fn install_quilt(
+    instance: &Instance,
+    loader_version: &str,
+) -> Result<QuiltInstallation, Box<dyn Error + Send + Sync>> {
+    let loader_info = get_quilt_loader_info(&instance.version_id, loader_version).await?;
+    let mut profile = get_quilt_profile(&instance.version_id, loader_version).await?;
+    let libraries_dir = get_libraries_dir();
+
+    // The profile normally lists the mappings, fall back to hashed if it doesn't
+    let has_mappings = profile.libraries.iter().any(|lib| {
+        lib.name == loader_info.hashed.maven || lib.name == loader_info.intermediary.maven
+    });
+    if !has_mappings {
+        profile.libraries.push(FabricLibrary {
+            name: loader_info.hashed.maven.clone(),
+            url: QUILT_MAVEN.to_string(),
+            sha1: None,
+            size: None,
+        });
+    }
+
+    // Download every profile library (loader, mappings and dependencies)
+    for lib in &profile.libraries {
+        let url = if lib.url.is_empty() { QUILT_MAVEN } else { lib.url.as_str() };
+        download_library_with_sha1(url, &lib.name, &libraries_dir, lib.sha1.as_deref()).await?;
+    }
+
+    let installation = QuiltInstallation {
+        loader: loader_info,
+        profile,
+    };
+
+    // Save Quilt info to instance folder for later use
+    let quilt_json_path = instance.get_directory().join("quilt.json");
+    let quilt_json = serde_json::to_string_pretty(&installation)?;
+    fs::write(&quilt_json_path, quilt_json)?;
+
+    Ok(installation)
+}
+
+/// Load saved Quilt info from instance
+pub fn load_quilt_info(instance: &Instance) -> Option<QuiltInstallation> {
+    let quilt_json_path = instance.get_directory().join("quilt.json");
+    if !quilt_json_path.exists() {
+        return None;
+    }
+
+    let content = fs::read_to_string(&quilt_json_path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Main class to launch Quilt with
+pub fn get_quilt_main_class(quilt_info: &QuiltInstallation) -> String {
+    if quilt_info.profile.main_class.is_empty() {
+        quilt_info.loader.launcher_meta.main_class.get_client_class().to_string()
+    } else {
+        quilt_info.profile.main_class.clone()
+    }
+}
+
+/// Quilt profile as an inheritsFrom version JSON for the version resolver
+pub fn quilt_version_json(quilt_info: &QuiltInstallation) -> serde_json::Value {
+    let mut json = serde_json::to_value(&quilt_info.profile).unwrap_or_default();
+    if let Some(obj) = json.as_object_mut() {
+        obj.insert("mainClass".to_string(), get_quilt_main_class(quilt_info).into());
+        if obj.get("arguments").map_or(false, |a| a.is_null()) {
+            obj.remove("arguments");
+        }
+        // Libraries without a repository come from the Quilt maven
+        if let Some(libs) = obj.get_mut("libraries").and_then(|l| l.as_array_mut()) {
+            for lib in libs {
+                if lib.get("url").and_then(|u| u.as_str()).map_or(true, |u| u.is_empty()) {
+                    lib["url"] = QUILT_MAVEN.into();
+                }
+            }
+        }
+    }
+    json
+}
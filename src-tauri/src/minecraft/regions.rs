@@ -0,0 +1,422 @@
+use crate::minecraft::backups::{self, WorldBackup};
+use crate::minecraft::files::get_saves_dir;
+use crate::minecraft::instances::Instance;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+const SECTOR_SIZE: usize = 4096;
+const HEADER_SIZE: usize = SECTOR_SIZE * 2;
+const CHUNKS_PER_REGION: usize = 1024;
+
+/// Upper bounds (in ticks) for the InhabitedTime buckets: <1m, <5m, <30m, <2h, rest
+const INHABITED_BUCKETS: [i64; 4] = [1_200, 6_000, 36_000, 144_000];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegionProgress {
+    pub instance_id: String,
+    pub folder_name: String,
+    pub stage: String,
+    pub current: u32,
+    pub total: u32,
+    pub percentage: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct InhabitedDistribution {
+    pub under_1_minute: u32,
+    pub under_5_minutes: u32,
+    pub under_30_minutes: u32,
+    pub under_2_hours: u32,
+    pub over_2_hours: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DimensionStats {
+    /// "minecraft:overworld", "minecraft:the_nether", ... or the custom dimension id
+    pub dimension: String,
+    pub region_files: u32,
+    /// Bytes used by region/, entities/ and poi/ files of this dimension
+    pub size: u64,
+    pub chunk_count: u32,
+    /// Chunks whose data could not be read (unsupported compression, corrupt data)
+    pub unreadable_chunks: u32,
+    pub inhabited: InhabitedDistribution,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorldRegionAnalysis {
+    pub folder_name: String,
+    pub total_size: u64,
+    pub total_chunks: u32,
+    pub dimensions: Vec<DimensionStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PruneResult {
+    pub backup: WorldBackup,
+    pub chunks_removed: u32,
+    pub bytes_freed: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkNbt {
+    // 1.18+ stores it at the root, older versions under "Level"
+    #[serde(rename = "InhabitedTime")]
+    inhabited_time: Option<i64>,
+    #[serde(rename = "Level")]
+    level: Option<LegacyChunkLevel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyChunkLevel {
+    #[serde(rename = "InhabitedTime")]
+    inhabited_time: Option<i64>,
+}
+
+/// A dimension folder of a world (the folder containing region/, entities/, poi/)
+struct DimensionDir {
+    id: String,
+    path: PathBuf,
+}
+
+fn emit_progress(app_handle: &AppHandle, instance: &Instance, folder_name: &str, stage: String, current: u32, total: u32) {
+    let _ = app_handle.emit("world-region-progress", RegionProgress {
+        instance_id: instance.id.clone(),
+        folder_name: folder_name.to_string(),
+        stage,
+        current,
+        total,
+        percentage: if total > 0 { (current as f32 / total as f32) * 100.0 } else { 100.0 },
+    });
+}
+
+/// Find every dimension of a world, including datapack dimensions under dimensions/
+fn find_dimensions(world_path: &Path) -> Vec<DimensionDir> {
+    let mut dims = vec![
+        DimensionDir { id: "minecraft:overworld".to_string(), path: world_path.to_path_buf() },
+        DimensionDir { id: "minecraft:the_nether".to_string(), path: world_path.join("DIM-1") },
+        DimensionDir { id: "minecraft:the_end".to_string(), path: world_path.join("DIM1") },
+    ];
+
+    // dimensions/<namespace>/<name>/region
+    if let Ok(namespaces) = fs::read_dir(world_path.join("dimensions")) {
+        for ns in namespaces.flatten() {
+            let ns_name = ns.file_name().to_string_lossy().to_string();
+            if let Ok(entries) = fs::read_dir(ns.path()) {
+                for entry in entries.flatten() {
+                    let id = format!("{}:{}", ns_name, entry.file_name().to_string_lossy());
+                    // Vanilla dimensions never live here, but skip them in case a tool put them there
+                    if dims.iter().any(|d| d.id == id) {
+                        continue;
+                    }
+                    dims.push(DimensionDir { id, path: entry.path() });
+                }
+            }
+        }
+    }
+
+    dims.into_iter()
+        .filter(|d| d.path.join("region").is_dir())
+        .collect()
+}
+
+/// List the .mca files in a directory
+fn list_region_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().map(|e| e == "mca").unwrap_or(false))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    files.sort();
+    files
+}
+
+fn dir_file_size(dir: &Path) -> u64 {
+    list_region_files(dir)
+        .iter()
+        .filter_map(|p| fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Location entry of a chunk in the region header: (sector offset, sector count)
+fn chunk_location(header: &[u8], index: usize) -> (usize, usize) {
+    let entry = &header[index * 4..index * 4 + 4];
+    let offset = ((entry[0] as usize) << 16) | ((entry[1] as usize) << 8) | entry[2] as usize;
+    (offset, entry[3] as usize)
+}
+
+/// Region coordinates from a file name like r.-1.2.mca
+fn region_coords(path: &Path) -> Option<(i32, i32)> {
+    let stem = path.file_stem()?.to_str()?;
+    let mut parts = stem.split('.');
+    if parts.next()? != "r" {
+        return None;
+    }
+    let x = parts.next()?.parse().ok()?;
+    let z = parts.next()?.parse().ok()?;
+    Some((x, z))
+}
+
+/// Decompress the NBT payload of a single chunk
+fn read_chunk_nbt(region_path: &Path, data: &[u8], offset: usize, index: usize) -> Option<Vec<u8>> {
+    let start = offset * SECTOR_SIZE;
+    if data.len() < start + 5 {
+        return None;
+    }
+    let length = u32::from_be_bytes([data[start], data[start + 1], data[start + 2], data[start + 3]]) as usize;
+    let compression = data[start + 4];
+    if length == 0 {
+        return None;
+    }
+
+    // Oversized chunks are stored next to the region file as c.<x>.<z>.mcc
+    let external;
+    let payload: &[u8] = if compression & 0x80 != 0 {
+        let (rx, rz) = region_coords(region_path)?;
+        let cx = rx * 32 + (index % 32) as i32;
+        let cz = rz * 32 + (index / 32) as i32;
+        external = fs::read(region_path.with_file_name(format!("c.{}.{}.mcc", cx, cz))).ok()?;
+        &external
+    } else {
+        let end = start + 4 + length;
+        if data.len() < end {
+            return None;
+        }
+        &data[start + 5..end]
+    };
+
+    let mut decoded = Vec::new();
+    match compression & 0x7F {
+        1 => GzDecoder::new(payload).read_to_end(&mut decoded).ok()?,
+        2 => ZlibDecoder::new(payload).read_to_end(&mut decoded).ok()?,
+        3 => {
+            decoded = payload.to_vec();
+            decoded.len()
+        }
+        // LZ4 (24w04a+) and custom compression aren't supported
+        _ => return None,
+    };
+    Some(decoded)
+}
+
+fn chunk_inhabited_time(nbt: &[u8]) -> Option<i64> {
+    let chunk: ChunkNbt = fastnbt::from_bytes(nbt).ok()?;
+    chunk.inhabited_time.or(chunk.level.and_then(|l| l.inhabited_time))
+}
+
+/// Read InhabitedTime for every chunk present in a region file (None if unreadable)
+fn scan_region(path: &Path) -> Result<Vec<(usize, Option<i64>)>, String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if data.len() < HEADER_SIZE {
+        // Empty or truncated region files are left behind by the game sometimes
+        return Ok(Vec::new());
+    }
+
+    let mut chunks = Vec::new();
+    for index in 0..CHUNKS_PER_REGION {
+        let (offset, count) = chunk_location(&data, index);
+        if offset == 0 || count == 0 {
+            continue;
+        }
+        let inhabited = read_chunk_nbt(path, &data, offset, index)
+            .and_then(|nbt| chunk_inhabited_time(&nbt));
+        chunks.push((index, inhabited));
+    }
+    Ok(chunks)
+}
+
+fn add_to_distribution(dist: &mut InhabitedDistribution, ticks: i64) {
+    if ticks < INHABITED_BUCKETS[0] {
+        dist.under_1_minute += 1;
+    } else if ticks < INHABITED_BUCKETS[1] {
+        dist.under_5_minutes += 1;
+    } else if ticks < INHABITED_BUCKETS[2] {
+        dist.under_30_minutes += 1;
+    } else if ticks < INHABITED_BUCKETS[3] {
+        dist.under_2_hours += 1;
+    } else {
+        dist.over_2_hours += 1;
+    }
+}
+
+/// Analyze region files of every dimension in a world
+pub fn analyze_world(app_handle: &AppHandle, instance: &Instance, folder_name: &str) -> Result<WorldRegionAnalysis, String> {
+    let world_path = get_saves_dir(instance).join(folder_name);
+    if !world_path.join("level.dat").exists() {
+        return Err("World folder not found".to_string());
+    }
+
+    let dims = find_dimensions(&world_path);
+    let work: Vec<(usize, PathBuf)> = dims.iter()
+        .enumerate()
+        .flat_map(|(i, d)| list_region_files(&d.path.join("region")).into_iter().map(move |p| (i, p)))
+        .collect();
+    let total = work.len() as u32;
+
+    let mut stats: Vec<DimensionStats> = dims.iter().map(|d| DimensionStats {
+        dimension: d.id.clone(),
+        region_files: 0,
+        size: dir_file_size(&d.path.join("region"))
+            + dir_file_size(&d.path.join("entities"))
+            + dir_file_size(&d.path.join("poi")),
+        chunk_count: 0,
+        unreadable_chunks: 0,
+        inhabited: InhabitedDistribution::default(),
+    }).collect();
+
+    for (done, (dim_index, path)) in work.iter().enumerate() {
+        emit_progress(app_handle, instance, folder_name, format!("Analyzing regions ({}/{})", done, total), done as u32, total);
+
+        let stat = &mut stats[*dim_index];
+        stat.region_files += 1;
+        for (_, inhabited) in scan_region(path)? {
+            stat.chunk_count += 1;
+            match inhabited {
+                Some(ticks) => add_to_distribution(&mut stat.inhabited, ticks),
+                None => stat.unreadable_chunks += 1,
+            }
+        }
+    }
+
+    emit_progress(app_handle, instance, folder_name, "Analysis complete".to_string(), total, total);
+
+    Ok(WorldRegionAnalysis {
+        folder_name: folder_name.to_string(),
+        total_size: stats.iter().map(|s| s.size).sum(),
+        total_chunks: stats.iter().map(|s| s.chunk_count).sum(),
+        dimensions: stats,
+    })
+}
+
+/// Rewrite a region file without the given chunks, compacting the remaining sectors.
+/// Returns the number of bytes freed. The file is removed once no chunks remain.
+fn remove_chunks_from_region(path: &Path, remove: &HashSet<usize>) -> Result<u64, String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let old_size = data.len() as u64;
+    if data.len() < HEADER_SIZE {
+        return Ok(0);
+    }
+
+    let mut header = vec![0u8; HEADER_SIZE];
+    let mut body: Vec<u8> = Vec::new();
+    let mut kept = 0;
+
+    for index in 0..CHUNKS_PER_REGION {
+        let (offset, count) = chunk_location(&data, index);
+        if offset == 0 || count == 0 || remove.contains(&index) {
+            continue;
+        }
+
+        let start = offset * SECTOR_SIZE;
+        let end = (start + count * SECTOR_SIZE).min(data.len());
+        if start >= end {
+            continue;
+        }
+
+        let new_offset = (HEADER_SIZE + body.len()) / SECTOR_SIZE;
+        body.extend_from_slice(&data[start..end]);
+        // Pad to a full sector if the original file was truncated
+        let padded = body.len().div_ceil(SECTOR_SIZE) * SECTOR_SIZE;
+        body.resize(padded, 0);
+
+        header[index * 4] = (new_offset >> 16) as u8;
+        header[index * 4 + 1] = (new_offset >> 8) as u8;
+        header[index * 4 + 2] = new_offset as u8;
+        header[index * 4 + 3] = count as u8;
+        // Keep the original timestamp
+        header[SECTOR_SIZE + index * 4..SECTOR_SIZE + index * 4 + 4]
+            .copy_from_slice(&data[SECTOR_SIZE + index * 4..SECTOR_SIZE + index * 4 + 4]);
+        kept += 1;
+    }
+
+    // External chunk files of removed chunks are no longer referenced
+    if let Some((rx, rz)) = region_coords(path) {
+        for index in remove {
+            let cx = rx * 32 + (index % 32) as i32;
+            let cz = rz * 32 + (index / 32) as i32;
+            let _ = fs::remove_file(path.with_file_name(format!("c.{}.{}.mcc", cx, cz)));
+        }
+    }
+
+    if kept == 0 {
+        fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        return Ok(old_size);
+    }
+
+    header.extend_from_slice(&body);
+    let new_size = header.len() as u64;
+
+    let temp_path = path.with_extension("mca.tmp");
+    fs::write(&temp_path, &header).map_err(|e| format!("Failed to write {}: {}", temp_path.display(), e))?;
+    fs::rename(&temp_path, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+
+    Ok(old_size.saturating_sub(new_size))
+}
+
+/// Delete every chunk whose InhabitedTime is below `min_inhabited_ticks`.
+/// A backup of the world is always taken first; nothing is touched if it fails.
+pub fn prune_world_chunks(app_handle: &AppHandle, instance: &Instance, folder_name: &str, min_inhabited_ticks: i64) -> Result<PruneResult, String> {
+    let world_path = get_saves_dir(instance).join(folder_name);
+    if !world_path.join("level.dat").exists() {
+        return Err("World folder not found".to_string());
+    }
+    if min_inhabited_ticks <= 0 {
+        return Err("Threshold must be greater than zero".to_string());
+    }
+
+    emit_progress(app_handle, instance, folder_name, "Backing up world".to_string(), 0, 1);
+    let backup = backups::backup_world(instance, folder_name, "pre-prune")?;
+
+    let work: Vec<(PathBuf, PathBuf)> = find_dimensions(&world_path)
+        .into_iter()
+        .flat_map(|d| list_region_files(&d.path.join("region")).into_iter().map(move |p| (d.path.clone(), p)))
+        .collect();
+    let total = work.len() as u32;
+
+    let mut chunks_removed = 0;
+    let mut bytes_freed = 0;
+
+    for (done, (dim_path, region_path)) in work.iter().enumerate() {
+        emit_progress(app_handle, instance, folder_name, format!("Pruning regions ({}/{})", done, total), done as u32, total);
+
+        // Unreadable chunks are kept, we can't tell how long they were played in
+        let remove: HashSet<usize> = scan_region(region_path)?
+            .into_iter()
+            .filter(|(_, inhabited)| matches!(inhabited, Some(ticks) if *ticks < min_inhabited_ticks))
+            .map(|(index, _)| index)
+            .collect();
+        if remove.is_empty() {
+            continue;
+        }
+
+        bytes_freed += remove_chunks_from_region(region_path, &remove)?;
+        chunks_removed += remove.len() as u32;
+
+        // Entities and POIs of the removed chunks live in sibling folders with the same file name
+        if let Some(file_name) = region_path.file_name() {
+            for sibling in ["entities", "poi"] {
+                let sibling_path = dim_path.join(sibling).join(file_name);
+                if sibling_path.exists() {
+                    bytes_freed += remove_chunks_from_region(&sibling_path, &remove)?;
+                }
+            }
+        }
+    }
+
+    emit_progress(app_handle, instance, folder_name, "Pruning complete".to_string(), total, total);
+
+    Ok(PruneResult {
+        backup,
+        chunks_removed,
+        bytes_freed,
+    })
+}
@@ -0,0 +1,195 @@
+use crate::minecraft::downloader::get_minecraft_dir;
+use crate::minecraft::files;
+use crate::minecraft::instances::Instance;
+use crate::minecraft::ping::{self, PingResponse};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+const CONCURRENT_PINGS: usize = 8;
+/// How long a cached MOTD/favicon is shown before it's considered stale
+const CACHE_TTL_SECS: u64 = 300;
+const LATENCY_HISTORY_LEN: usize = 50;
+
+/// Overlapping refreshes each load, update and save the cache under this lock
+static CACHE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LatencySample {
+    pub timestamp: u64,
+    /// None when the server didn't respond
+    pub latency_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CachedServerStatus {
+    pub address: String,
+    pub motd: Option<String>,
+    pub favicon: Option<String>,
+    pub version_name: Option<String>,
+    pub protocol_version: Option<i32>,
+    pub online_players: Option<i32>,
+    pub max_players: Option<i32>,
+    /// Last successful ping
+    pub updated_at: u64,
+    #[serde(default)]
+    pub latency_history: Vec<LatencySample>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServerListEntry {
+    pub instance_id: String,
+    pub name: String,
+    pub address: String,
+    /// Cached status, present while it's still within the TTL
+    pub cached: Option<CachedServerStatus>,
+    /// Protocol of the instance the server belongs to
+    #[serde(skip)]
+    pub protocol: Option<i32>,
+}
+
+/// Emitted as "server-ping-result" for every server as soon as its ping finishes
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServerPingResult {
+    pub instance_id: String,
+    pub name: String,
+    pub address: String,
+    pub status: Option<PingResponse>,
+    pub error: Option<String>,
+    pub latency_history: Vec<LatencySample>,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn get_cache_path() -> PathBuf {
+    get_minecraft_dir().join("server_status_cache.json")
+}
+
+fn cache_key(address: &str) -> String {
+    address.trim().to_lowercase()
+}
+
+fn load_cache() -> HashMap<String, CachedServerStatus> {
+    fs::read_to_string(get_cache_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(cache: &HashMap<String, CachedServerStatus>) -> Result<(), String> {
+    let json = serde_json::to_string(cache).map_err(|e| e.to_string())?;
+    fs::write(get_cache_path(), json).map_err(|e| format!("Failed to save server cache: {}", e))
+}
+
+/// Collect the server entries of one instance, or of every instance
+pub fn collect_servers(instances: &[Instance]) -> Vec<ServerListEntry> {
+    let cache = load_cache();
+    let now = now_secs();
+
+    instances.iter()
+        .flat_map(|instance| {
+            let protocol = ping::protocol_for_version(&instance.version_id);
+            files::list_servers(instance).into_iter().map(move |server| (instance.id.clone(), protocol, server))
+        })
+        .map(|(instance_id, protocol, server)| {
+            let cached = cache.get(&cache_key(&server.ip))
+                .filter(|c| now.saturating_sub(c.updated_at) < CACHE_TTL_SECS)
+                .cloned();
+            ServerListEntry {
+                instance_id,
+                name: server.name,
+                address: server.ip,
+                cached,
+                protocol,
+            }
+        })
+        .collect()
+}
+
+/// Get the latency history recorded for a server address
+pub fn get_latency_history(address: &str) -> Vec<LatencySample> {
+    load_cache()
+        .remove(&cache_key(address))
+        .map(|c| c.latency_history)
+        .unwrap_or_default()
+}
+
+fn record_result(cache: &mut HashMap<String, CachedServerStatus>, address: &str, result: &Result<PingResponse, String>) -> Vec<LatencySample> {
+    let now = now_secs();
+    let entry = cache.entry(cache_key(address)).or_insert_with(|| CachedServerStatus {
+        address: address.trim().to_string(),
+        ..Default::default()
+    });
+
+    if let Ok(status) = result {
+        entry.motd = Some(status.motd.clone());
+        entry.favicon = status.favicon.clone();
+        entry.version_name = Some(status.version_name.clone());
+        entry.protocol_version = Some(status.protocol_version);
+        entry.online_players = Some(status.online_players);
+        entry.max_players = Some(status.max_players);
+        entry.updated_at = now;
+    }
+
+    entry.latency_history.push(LatencySample {
+        timestamp: now,
+        latency_ms: result.as_ref().ok().map(|s| s.latency_ms),
+    });
+    if entry.latency_history.len() > LATENCY_HISTORY_LEN {
+        let excess = entry.latency_history.len() - LATENCY_HISTORY_LEN;
+        entry.latency_history.drain(..excess);
+    }
+
+    entry.latency_history.clone()
+}
+
+/// Ping every server concurrently, emitting "server-ping-result" as each one finishes
+/// and "server-ping-complete" once all are done. Each address is only pinged once.
+pub async fn ping_servers(app_handle: AppHandle, servers: Vec<ServerListEntry>) {
+    let mut addresses: Vec<(String, Option<i32>)> = servers.iter().map(|s| (s.address.clone(), s.protocol)).collect();
+    addresses.sort_by_key(|(a, _)| cache_key(a));
+    addresses.dedup_by_key(|(a, _)| cache_key(a));
+
+    let mut results = stream::iter(addresses)
+        .map(|(address, protocol)| async move {
+            let result = ping::ping_server(&address, protocol).await;
+            (address, result)
+        })
+        .buffer_unordered(CONCURRENT_PINGS);
+
+    while let Some((address, result)) = results.next().await {
+        // Re-read the cache for every result so pings from other refreshes aren't overwritten
+        let history = {
+            let _guard = CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let mut cache = load_cache();
+            let history = record_result(&mut cache, &address, &result);
+            if let Err(e) = save_cache(&cache) {
+                log::warn!("{}", e);
+            }
+            history
+        };
+
+        for server in servers.iter().filter(|s| cache_key(&s.address) == cache_key(&address)) {
+            let _ = app_handle.emit("server-ping-result", ServerPingResult {
+                instance_id: server.instance_id.clone(),
+                name: server.name.clone(),
+                address: server.address.clone(),
+                status: result.as_ref().ok().cloned(),
+                error: result.as_ref().err().cloned(),
+                latency_history: history.clone(),
+            });
+        }
+    }
+
+    let _ = app_handle.emit("server-ping-complete", servers.len());
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use crate::minecraft::downloader::get_minecraft_dir;
@@ -22,6 +23,17 @@ pub struct LauncherSettings {
     pub background_style: Option<String>,
     pub edit_mode_preference: Option<String>,
     pub enable_instance_animations: Option<bool>,
+    /// DNS server used for server address lookups instead of the system resolver
+    pub dns_server: Option<String>,
+    /// Maven repository for ARM64 LWJGL builds, defaults to Maven Central for LWJGL 3
+    pub natives_repository: Option<String>,
+    /// Keep the XML layout of the official log4j config on the game's stdout
+    pub log_xml_layout: Option<bool>,
+    /// Defaults for instances that don't set their own wrapper, environment or hooks
+    pub wrapper_command: Option<String>,
+    pub env_vars: Option<HashMap<String, String>>,
+    pub pre_launch_command: Option<String>,
+    pub post_exit_command: Option<String>,
 }
 
 fn default_update_channel() -> Option<String> {
@@ -42,6 +54,13 @@ impl Default for LauncherSettings {
             background_style: Some("gradient".to_string()),
             edit_mode_preference: Some("ask".to_string()),
             enable_instance_animations: Some(true),
+            dns_server: None,
+            natives_repository: None,
+            log_xml_layout: Some(false),
+            wrapper_command: None,
+            env_vars: None,
+            pre_launch_command: None,
+            post_exit_command: None,
         }
     }
 }
@@ -48,6 +48,18 @@ pub struct VersionDetails {
     #[serde(rename = "minecraftArguments")]
     pub minecraft_arguments: Option<String>,
     pub java_version: Option<JavaVersion>,
+    /// Parent version this one extends (Forge, Fabric, OptiFine...)
+    pub inherits_from: Option<String>,
+    /// Version whose client jar is used, set when resolving an inheritsFrom chain
+    pub jar: Option<String>,
+    pub logging: Option<Logging>,
+}
+
+impl VersionDetails {
+    /// ID of the version folder holding the client jar
+    pub fn client_jar_id(&self) -> &str {
+        self.jar.as_deref().unwrap_or(&self.id)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -102,11 +114,16 @@ pub struct Artifact {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Rule {
+    #[serde(default = "default_rule_action")]
     pub action: String,
     pub os: Option<OsRule>,
     pub features: Option<std::collections::HashMap<String, bool>>,
 }
 
+fn default_rule_action() -> String {
+    "allow".to_string()
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OsRule {
     pub name: Option<String>,
@@ -125,6 +142,27 @@ pub struct Arguments {
     pub jvm: Option<Vec<serde_json::Value>>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Logging {
+    pub client: Option<LoggingConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoggingConfig {
+    pub argument: String,
+    pub file: LoggingFile,
+    #[serde(rename = "type")]
+    pub config_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoggingFile {
+    pub id: String,
+    pub sha1: String,
+    pub size: i64,
+    pub url: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct JavaVersion {
@@ -159,6 +197,136 @@ pub fn load_version_details(path: &std::path::PathBuf) -> Result<VersionDetails,
     Ok(details)
 }
 
+/// Extract identity (group:artifact[:classifier]) from a maven name.
+/// The version is left out so different versions of the same library collide,
+/// the classifier is kept so native JARs are preserved.
+pub fn get_lib_identity(name: &str) -> String {
+    let parts: Vec<&str> = name.split(':').collect();
+    if parts.len() >= 4 {
+        // group:artifact:version:classifier -> group:artifact:classifier
+        format!("{}:{}:{}", parts[0], parts[1], parts[3])
+    } else if parts.len() >= 2 {
+        // group:artifact:version -> group:artifact
+        format!("{}:{}", parts[0], parts[1])
+    } else {
+        name.to_string()
+    }
+}
+
+/// Read a version JSON from the versions folder without parsing it
+pub fn load_version_json(version_id: &str) -> Result<serde_json::Value, String> {
+    let path = super::downloader::get_versions_dir()
+        .join(version_id)
+        .join(format!("{}.json", version_id));
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read version JSON for {}: {}", version_id, e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse version JSON for {}: {}", version_id, e))
+}
+
+/// Merge a child version JSON on top of its parent, following the official launcher:
+/// libraries are child first with parent duplicates dropped, argument lists are
+/// appended and every other key is taken from the child when present. Game arguments
+/// that are a complete set on their own (they contain --username) replace the parent's.
+fn merge_version_json(parent: serde_json::Value, child: serde_json::Value) -> serde_json::Value {
+    let (mut merged, child) = match (parent, child) {
+        (serde_json::Value::Object(p), serde_json::Value::Object(c)) => (p, c),
+        (_, child) => return child,
+    };
+
+    // The child reuses the parent's client jar unless it ships its own
+    let jar = child.get("jar").cloned()
+        .or_else(|| child.get("downloads").and(child.get("id")).cloned())
+        .or_else(|| merged.get("jar").cloned())
+        .or_else(|| merged.get("id").cloned());
+
+    for (key, value) in child {
+        match key.as_str() {
+            "libraries" => {
+                let child_libs = value.as_array().cloned().unwrap_or_default();
+                let parent_libs = merged.get("libraries").and_then(|l| l.as_array()).cloned().unwrap_or_default();
+                let identities: std::collections::HashSet<String> = child_libs.iter()
+                    .filter_map(|l| l.get("name").and_then(|n| n.as_str()))
+                    .map(get_lib_identity)
+                    .collect();
+
+                let mut libraries = child_libs;
+                libraries.extend(parent_libs.into_iter().filter(|l| {
+                    l.get("name")
+                        .and_then(|n| n.as_str())
+                        .map_or(true, |n| !identities.contains(&get_lib_identity(n)))
+                }));
+                merged.insert(key, serde_json::Value::Array(libraries));
+            }
+            "arguments" => {
+                let mut arguments = merged.get("arguments").and_then(|a| a.as_object()).cloned().unwrap_or_default();
+                if let Some(child_args) = value.as_object() {
+                    for (kind, list) in child_args {
+                        let list = list.as_array().cloned().unwrap_or_default();
+                        let complete_set = kind == "game" && list.iter().any(|v| v.as_str() == Some("--username"));
+                        let mut combined = if complete_set {
+                            Vec::new()
+                        } else {
+                            arguments.get(kind).and_then(|l| l.as_array()).cloned().unwrap_or_default()
+                        };
+                        combined.extend(list);
+                        arguments.insert(kind.clone(), serde_json::Value::Array(combined));
+                    }
+                }
+                merged.insert(key, serde_json::Value::Object(arguments));
+            }
+            "inheritsFrom" => {}
+            _ => {
+                merged.insert(key, value);
+            }
+        }
+    }
+
+    merged.remove("inheritsFrom");
+    if let Some(jar) = jar {
+        merged.insert("jar".to_string(), jar);
+    }
+    serde_json::Value::Object(merged)
+}
+
+/// Resolve a version JSON by following its inheritsFrom chain down to the root
+/// and merging every level on top of it
+pub fn resolve_version_json(json: serde_json::Value) -> Result<VersionDetails, String> {
+    resolve_with(json, load_version_json)
+}
+
+fn resolve_with(
+    json: serde_json::Value,
+    load: impl Fn(&str) -> Result<serde_json::Value, String>,
+) -> Result<VersionDetails, String> {
+    let mut chain = vec![json];
+    let mut seen: Vec<String> = Vec::new();
+
+    while let Some(parent_id) = chain.last()
+        .and_then(|v| v.get("inheritsFrom"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+    {
+        if seen.contains(&parent_id) {
+            return Err(format!("Version {} inherits from itself", parent_id));
+        }
+        seen.push(parent_id.clone());
+        chain.push(load(&parent_id)?);
+    }
+
+    let mut merged = chain.pop().ok_or("Empty version chain")?;
+    while let Some(child) = chain.pop() {
+        merged = merge_version_json(merged, child);
+    }
+
+    serde_json::from_value(merged).map_err(|e| format!("Failed to parse version JSON: {}", e))
+}
+
+/// Load a version from the versions folder with its full inheritsFrom chain applied
+pub fn resolve_version(version_id: &str) -> Result<VersionDetails, String> {
+    resolve_version_json(load_version_json(version_id)?)
+}
+
 /// Get the current OS name for library rules
 pub fn get_os_name() -> &'static str {
     if cfg!(target_os = "windows") {
@@ -170,36 +338,202 @@ pub fn get_os_name() -> &'static str {
     }
 }
 
+/// Architecture name used by os.arch rules
+pub fn get_os_arch() -> &'static str {
+    if cfg!(target_arch = "x86_64") {
+        "x86_64"
+    } else if cfg!(target_arch = "x86") {
+        "x86"
+    } else if cfg!(target_arch = "aarch64") {
+        "arm64"
+    } else if cfg!(target_arch = "arm") {
+        "arm32"
+    } else {
+        std::env::consts::ARCH
+    }
+}
+
+/// Value substituted for ${arch} in native classifiers
+pub fn get_arch_bits() -> &'static str {
+    if cfg!(target_pointer_width = "64") { "64" } else { "32" }
+}
+
+static OS_VERSION: std::sync::LazyLock<String> = std::sync::LazyLock::new(detect_os_version);
+
+/// OS version as Java reports it in os.version, e.g. "10.0" or "14.2.1"
+fn detect_os_version() -> String {
+    let output = if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "ver"]);
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            command.creation_flags(0x08000000);
+        }
+        command.output()
+    } else if cfg!(target_os = "macos") {
+        std::process::Command::new("sw_vers").arg("-productVersion").output()
+    } else {
+        std::process::Command::new("uname").arg("-r").output()
+    };
+
+    let text = output.map(|o| String::from_utf8_lossy(&o.stdout).to_string()).unwrap_or_default();
+    // "Microsoft Windows [Version 10.0.19045.3803]"
+    let text = text.rsplit("Version ").next().unwrap_or(&text);
+    text.trim().trim_end_matches(']').to_string()
+}
+
+pub fn get_os_version() -> &'static str {
+    &OS_VERSION
+}
+
+/// Feature flags that argument and library rules can test
+#[derive(Debug, Default, Clone)]
+pub struct RuleFeatures {
+    pub is_demo_user: bool,
+    pub has_custom_resolution: bool,
+    pub has_quick_plays_support: bool,
+    pub is_quick_play_singleplayer: bool,
+    pub is_quick_play_multiplayer: bool,
+    pub is_quick_play_realms: bool,
+}
+
+impl RuleFeatures {
+    pub fn is_enabled(&self, feature: &str) -> bool {
+        match feature {
+            "is_demo_user" => self.is_demo_user,
+            "has_custom_resolution" => self.has_custom_resolution,
+            "has_quick_plays_support" => self.has_quick_plays_support,
+            "is_quick_play_singleplayer" => self.is_quick_play_singleplayer,
+            "is_quick_play_multiplayer" => self.is_quick_play_multiplayer,
+            "is_quick_play_realms" => self.is_quick_play_realms,
+            _ => false,
+        }
+    }
+}
+
+/// OS, architecture and version that os rules and native classifiers are checked against
+#[derive(Debug, Clone)]
+struct Platform<'a> {
+    name: &'a str,
+    arch: &'a str,
+    version: &'a str,
+    arch_bits: &'a str,
+}
+
+impl Platform<'static> {
+    fn current() -> Self {
+        Platform {
+            name: get_os_name(),
+            arch: get_os_arch(),
+            version: get_os_version(),
+            arch_bits: get_arch_bits(),
+        }
+    }
+}
+
+/// Compiled os.version patterns, None for patterns that failed to compile
+static OS_VERSION_PATTERNS: std::sync::LazyLock<std::sync::Mutex<std::collections::HashMap<String, Option<regex::Regex>>>> =
+    std::sync::LazyLock::new(|| std::sync::Mutex::new(std::collections::HashMap::new()));
+
+fn os_version_matches(pattern: &str, version: &str) -> bool {
+    let mut patterns = OS_VERSION_PATTERNS.lock().unwrap_or_else(|e| e.into_inner());
+    let compiled = patterns.entry(pattern.to_string()).or_insert_with(|| match regex::Regex::new(pattern) {
+        Ok(re) => Some(re),
+        Err(e) => {
+            log::warn!("Invalid os.version rule '{}': {}", pattern, e);
+            None
+        }
+    });
+    compiled.as_ref().is_some_and(|re| re.is_match(version))
+}
+
+fn os_rule_matches(os: &OsRule, platform: &Platform) -> bool {
+    if let Some(name) = &os.name {
+        if name != platform.name {
+            return false;
+        }
+    }
+    if let Some(arch) = &os.arch {
+        if arch != platform.arch {
+            return false;
+        }
+    }
+    if let Some(version) = &os.version {
+        if !os_version_matches(version, platform.version) {
+            return false;
+        }
+    }
+    true
+}
+
+fn rule_matches(rule: &Rule, features: &RuleFeatures, platform: &Platform) -> bool {
+    if let Some(os) = &rule.os {
+        if !os_rule_matches(os, platform) {
+            return false;
+        }
+    }
+    // Every listed feature must match its expected value
+    if let Some(required) = &rule.features {
+        if required.iter().any(|(feature, expected)| features.is_enabled(feature) != *expected) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Evaluate a rule list like the official launcher: disallowed unless a rule matches,
+/// the last matching rule wins
+pub fn rules_allow(rules: &[Rule], features: &RuleFeatures) -> bool {
+    rules_allow_on(rules, features, &Platform::current())
+}
+
+fn rules_allow_on(rules: &[Rule], features: &RuleFeatures, platform: &Platform) -> bool {
+    let mut allowed = false;
+    for rule in rules {
+        if rule_matches(rule, features, platform) {
+            allowed = rule.action == "allow";
+        }
+    }
+    allowed
+}
+
+/// Check the rules of a conditional argument ({"rules": [...], "value": ...})
+pub fn argument_allowed(arg: &serde_json::Map<String, serde_json::Value>, features: &RuleFeatures) -> bool {
+    argument_allowed_on(arg, features, &Platform::current())
+}
+
+fn argument_allowed_on(arg: &serde_json::Map<String, serde_json::Value>, features: &RuleFeatures, platform: &Platform) -> bool {
+    match arg.get("rules") {
+        None => true,
+        Some(rules) => match serde_json::from_value::<Vec<Rule>>(rules.clone()) {
+            Ok(rules) => rules_allow_on(&rules, features, platform),
+            Err(e) => {
+                log::warn!("Skipping argument with unreadable rules: {}", e);
+                false
+            }
+        },
+    }
+}
+
 /// Check if a library should be included based on its rules
 pub fn should_use_library(library: &Library) -> bool {
-    let os_name = get_os_name();
-    
     match &library.rules {
         None => true,
-        Some(rules) => {
-            let mut dominated_result = false;
-            
-            for rule in rules {
-                let rule_applies = match &rule.os {
-                    None => true,
-                    Some(os_rule) => {
-                        match &os_rule.name {
-                            None => true,
-                            Some(name) => name == os_name,
-                        }
-                    }
-                };
-                
-                if rule_applies {
-                    dominated_result = rule.action == "allow";
-                }
-            }
-            
-            dominated_result
-        }
+        Some(rules) => rules_allow(rules, &RuleFeatures::default()),
     }
 }
 
+/// Native classifier key for this OS with ${arch} filled in, e.g. "natives-windows-64"
+pub fn native_classifier(library: &Library) -> Option<String> {
+    native_classifier_on(library, &Platform::current())
+}
+
+fn native_classifier_on(library: &Library, platform: &Platform) -> Option<String> {
+    let key = library.natives.as_ref()?.get(platform.name)?;
+    Some(key.replace("${arch}", platform.arch_bits))
+}
+
 /// Convert library name (group:artifact:version) to path
 pub fn library_name_to_path(name: &str) -> String {
     let parts: Vec<&str> = name.split(':').collect();
@@ -222,3 +556,366 @@ pub fn library_name_to_path(name: &str) -> String {
     path.push_str(".jar");
     path
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn vanilla_1_20_1() -> serde_json::Value {
+        json!({
+            "id": "1.20.1",
+            "type": "release",
+            "mainClass": "net.minecraft.client.main.Main",
+            "releaseTime": "2023-06-12T13:25:51+00:00",
+            "time": "2023-06-12T13:25:51+00:00",
+            "assets": "5",
+            "assetIndex": { "id": "5", "sha1": "a", "size": 1, "url": "https://example.invalid/5.json" },
+            "downloads": { "client": { "sha1": "b", "size": 1, "url": "https://example.invalid/client.jar" } },
+            "libraries": [
+                { "name": "org.ow2.asm:asm:9.3" },
+                { "name": "com.mojang:brigadier:1.1.8" },
+                { "name": "org.lwjgl:lwjgl:3.3.1:natives-linux" }
+            ],
+            "arguments": {
+                "game": ["--username", "${auth_player_name}", "--version", "${version_name}"],
+                "jvm": ["-Djava.library.path=${natives_directory}", "-cp", "${classpath}"]
+            }
+        })
+    }
+
+    fn fabric_child() -> serde_json::Value {
+        json!({
+            "id": "fabric-loader-0.15.0-1.20.1",
+            "inheritsFrom": "1.20.1",
+            "type": "release",
+            "mainClass": "net.fabricmc.loader.impl.launch.knot.KnotClient",
+            "releaseTime": "2023-12-01T00:00:00+00:00",
+            "time": "2023-12-01T00:00:00+00:00",
+            "libraries": [
+                { "name": "org.ow2.asm:asm:9.6" },
+                { "name": "net.fabricmc:fabric-loader:0.15.0" }
+            ],
+            "arguments": {
+                "game": [],
+                "jvm": ["-DFabricMcEmu= net.minecraft.client.main.Main "]
+            }
+        })
+    }
+
+    fn resolve(child: serde_json::Value, parents: &[serde_json::Value]) -> Result<VersionDetails, String> {
+        let parents: HashMap<String, serde_json::Value> = parents.iter()
+            .map(|p| (p["id"].as_str().unwrap().to_string(), p.clone()))
+            .collect();
+        resolve_with(child, |id| parents.get(id).cloned().ok_or(format!("Version {} is not installed", id)))
+    }
+
+    fn library_names(details: &VersionDetails) -> Vec<&str> {
+        details.libraries.iter().map(|l| l.name.as_str()).collect()
+    }
+
+    fn strings(args: &Option<Vec<serde_json::Value>>) -> Vec<&str> {
+        args.as_ref().unwrap().iter().filter_map(|v| v.as_str()).collect()
+    }
+
+    #[test]
+    fn libraries_child_first_deduplicated_by_group_artifact() {
+        let details = resolve(fabric_child(), &[vanilla_1_20_1()]).unwrap();
+        assert_eq!(library_names(&details), vec![
+            "org.ow2.asm:asm:9.6",
+            "net.fabricmc:fabric-loader:0.15.0",
+            "com.mojang:brigadier:1.1.8",
+            "org.lwjgl:lwjgl:3.3.1:natives-linux",
+        ]);
+    }
+
+    #[test]
+    fn argument_lists_are_appended_after_the_parent() {
+        let details = resolve(fabric_child(), &[vanilla_1_20_1()]).unwrap();
+        let arguments = details.arguments.unwrap();
+        assert_eq!(strings(&arguments.game), vec!["--username", "${auth_player_name}", "--version", "${version_name}"]);
+        assert_eq!(strings(&arguments.jvm), vec![
+            "-Djava.library.path=${natives_directory}",
+            "-cp",
+            "${classpath}",
+            "-DFabricMcEmu= net.minecraft.client.main.Main ",
+        ]);
+    }
+
+    #[test]
+    fn complete_game_argument_set_replaces_the_parent() {
+        let mut child = fabric_child();
+        child["arguments"]["game"] = json!(["--username", "${auth_player_name}", "--tweakClass", "optifine.OptiFineTweaker"]);
+        let details = resolve(child, &[vanilla_1_20_1()]).unwrap();
+        assert_eq!(
+            strings(&details.arguments.unwrap().game),
+            vec!["--username", "${auth_player_name}", "--tweakClass", "optifine.OptiFineTweaker"]
+        );
+    }
+
+    #[test]
+    fn scalars_are_taken_from_the_child() {
+        let mut child = fabric_child();
+        child["type"] = json!("snapshot");
+        child["assetIndex"] = json!({ "id": "6", "sha1": "c", "size": 2, "url": "https://example.invalid/6.json" });
+        let details = resolve(child, &[vanilla_1_20_1()]).unwrap();
+        assert_eq!(details.main_class, "net.fabricmc.loader.impl.launch.knot.KnotClient");
+        assert_eq!(details.version_type, "snapshot");
+        assert_eq!(details.asset_index.unwrap().id, "6");
+        assert_eq!(details.id, "fabric-loader-0.15.0-1.20.1");
+        assert!(details.inherits_from.is_none());
+    }
+
+    #[test]
+    fn scalars_missing_in_the_child_come_from_the_parent() {
+        let details = resolve(fabric_child(), &[vanilla_1_20_1()]).unwrap();
+        assert_eq!(details.version_type, "release");
+        assert_eq!(details.asset_index.unwrap().id, "5");
+        assert_eq!(details.assets.as_deref(), Some("5"));
+    }
+
+    #[test]
+    fn client_jar_is_inherited_from_the_root() {
+        let details = resolve(fabric_child(), &[vanilla_1_20_1()]).unwrap();
+        assert_eq!(details.jar.as_deref(), Some("1.20.1"));
+        assert_eq!(details.client_jar_id(), "1.20.1");
+    }
+
+    #[test]
+    fn client_jar_follows_the_whole_chain() {
+        let mut optifine = fabric_child();
+        optifine["id"] = json!("1.20.1-OptiFine");
+        let mut on_top = fabric_child();
+        on_top["id"] = json!("1.20.1-OptiFine-custom");
+        on_top["inheritsFrom"] = json!("1.20.1-OptiFine");
+        let details = resolve(on_top, &[vanilla_1_20_1(), optifine]).unwrap();
+        assert_eq!(details.client_jar_id(), "1.20.1");
+    }
+
+    #[test]
+    fn child_with_its_own_client_download_keeps_its_jar() {
+        let mut child = fabric_child();
+        child["downloads"] = json!({ "client": { "sha1": "d", "size": 1, "url": "https://example.invalid/custom.jar" } });
+        let details = resolve(child, &[vanilla_1_20_1()]).unwrap();
+        assert_eq!(details.client_jar_id(), "fabric-loader-0.15.0-1.20.1");
+
+        let mut child = fabric_child();
+        child["jar"] = json!("1.20.1-patched");
+        let details = resolve(child, &[vanilla_1_20_1()]).unwrap();
+        assert_eq!(details.client_jar_id(), "1.20.1-patched");
+    }
+
+    #[test]
+    fn version_without_parent_uses_its_own_jar() {
+        let details = resolve(vanilla_1_20_1(), &[]).unwrap();
+        assert!(details.jar.is_none());
+        assert_eq!(details.client_jar_id(), "1.20.1");
+    }
+
+    #[test]
+    fn legacy_minecraft_arguments_are_overridden() {
+        let vanilla = json!({
+            "id": "1.12.2",
+            "type": "release",
+            "mainClass": "net.minecraft.client.main.Main",
+            "releaseTime": "2017-09-18T08:39:46+00:00",
+            "time": "2017-09-18T08:39:46+00:00",
+            "libraries": [],
+            "minecraftArguments": "--username ${auth_player_name} --version ${version_name}"
+        });
+        let forge = json!({
+            "id": "1.12.2-forge-14.23.5.2860",
+            "inheritsFrom": "1.12.2",
+            "type": "release",
+            "mainClass": "net.minecraft.launchwrapper.Launch",
+            "releaseTime": "2021-12-01T00:00:00+00:00",
+            "time": "2021-12-01T00:00:00+00:00",
+            "libraries": [],
+            "minecraftArguments": "--username ${auth_player_name} --version ${version_name} --tweakClass net.minecraftforge.fml.common.launcher.FMLTweaker"
+        });
+        let details = resolve(forge, &[vanilla]).unwrap();
+        assert_eq!(details.main_class, "net.minecraft.launchwrapper.Launch");
+        assert_eq!(
+            details.minecraft_arguments.as_deref(),
+            Some("--username ${auth_player_name} --version ${version_name} --tweakClass net.minecraftforge.fml.common.launcher.FMLTweaker")
+        );
+        assert!(details.arguments.is_none());
+    }
+
+    #[test]
+    fn inheritance_cycle_is_an_error() {
+        let mut a = fabric_child();
+        a["id"] = json!("a");
+        a["inheritsFrom"] = json!("b");
+        let mut b = fabric_child();
+        b["id"] = json!("b");
+        b["inheritsFrom"] = json!("a");
+        let err = resolve(a.clone(), &[a, b]).unwrap_err();
+        assert!(err.contains("inherits from itself"), "{}", err);
+    }
+
+    #[test]
+    fn missing_parent_is_an_error() {
+        let err = resolve(fabric_child(), &[]).unwrap_err();
+        assert!(err.contains("1.20.1"), "{}", err);
+    }
+
+    const LINUX: Platform<'static> = Platform { name: "linux", arch: "x86_64", version: "6.5.0", arch_bits: "64" };
+    const WINDOWS_10: Platform<'static> = Platform { name: "windows", arch: "x86_64", version: "10.0", arch_bits: "64" };
+    const WINDOWS_7_X86: Platform<'static> = Platform { name: "windows", arch: "x86", version: "6.1", arch_bits: "32" };
+    const MACOS: Platform<'static> = Platform { name: "osx", arch: "arm64", version: "14.2.1", arch_bits: "64" };
+
+    fn rules(value: serde_json::Value) -> Vec<Rule> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn argument(value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn library_rules_from_1_12_2() {
+        // org.lwjgl.lwjgl:lwjgl:2.9.4-nightly-20150209 everywhere but macOS
+        let lwjgl = rules(json!([
+            { "action": "allow" },
+            { "action": "disallow", "os": { "name": "osx" } }
+        ]));
+        // org.lwjgl.lwjgl:lwjgl:2.9.2-nightly-20140822 only on macOS
+        let lwjgl_osx = rules(json!([
+            { "action": "allow", "os": { "name": "osx" } }
+        ]));
+
+        let cases = [
+            (&lwjgl, &LINUX, true),
+            (&lwjgl, &WINDOWS_10, true),
+            (&lwjgl, &MACOS, false),
+            (&lwjgl_osx, &LINUX, false),
+            (&lwjgl_osx, &WINDOWS_10, false),
+            (&lwjgl_osx, &MACOS, true),
+        ];
+        for (rules, platform, expected) in cases {
+            assert_eq!(rules_allow_on(rules, &RuleFeatures::default(), platform), expected, "{:?} on {:?}", rules, platform);
+        }
+    }
+
+    #[test]
+    fn jvm_argument_rules_from_1_19() {
+        let xss = argument(json!({
+            "rules": [{ "action": "allow", "os": { "arch": "x86" } }],
+            "value": "-Xss1M"
+        }));
+        let windows_10 = argument(json!({
+            "rules": [{ "action": "allow", "os": { "name": "windows", "version": "^10\\." } }],
+            "value": ["-Dos.name=Windows 10", "-Dos.version=10.0"]
+        }));
+        let macos = argument(json!({
+            "rules": [{ "action": "allow", "os": { "name": "osx" } }],
+            "value": ["-XstartOnFirstThread"]
+        }));
+        let plain = argument(json!({ "value": "-Djava.library.path=${natives_directory}" }));
+
+        let cases = [
+            (&xss, &WINDOWS_7_X86, true),
+            (&xss, &WINDOWS_10, false),
+            (&xss, &LINUX, false),
+            (&windows_10, &WINDOWS_10, true),
+            (&windows_10, &WINDOWS_7_X86, false),
+            (&windows_10, &Platform { name: "linux", arch: "x86_64", version: "10.1", arch_bits: "64" }, false),
+            (&windows_10, &Platform { name: "windows", arch: "x86_64", version: "110.0", arch_bits: "64" }, false),
+            (&macos, &MACOS, true),
+            (&macos, &LINUX, false),
+            (&plain, &LINUX, true),
+        ];
+        for (arg, platform, expected) in cases {
+            assert_eq!(argument_allowed_on(arg, &RuleFeatures::default(), platform), expected, "{:?} on {:?}", arg, platform);
+        }
+    }
+
+    #[test]
+    fn invalid_os_version_pattern_never_matches() {
+        let arg = argument(json!({
+            "rules": [{ "action": "allow", "os": { "version": "^10\\.(" } }],
+            "value": "-Dbroken"
+        }));
+        assert!(!argument_allowed_on(&arg, &RuleFeatures::default(), &WINDOWS_10));
+        assert!(!argument_allowed_on(&arg, &RuleFeatures::default(), &WINDOWS_10));
+    }
+
+    #[test]
+    fn game_argument_feature_rules() {
+        let feature_arg = |feature: &str| argument(json!({
+            "rules": [{ "action": "allow", "features": { feature: true } }],
+            "value": ["--flag"]
+        }));
+        let demo = feature_arg("is_demo_user");
+        let resolution = feature_arg("has_custom_resolution");
+        let singleplayer = feature_arg("is_quick_play_singleplayer");
+        let multiplayer = feature_arg("is_quick_play_multiplayer");
+        let realms = feature_arg("is_quick_play_realms");
+        let quick_plays = feature_arg("has_quick_plays_support");
+        let unknown = feature_arg("is_some_future_feature");
+
+        let none = RuleFeatures::default();
+        let demo_user = RuleFeatures { is_demo_user: true, ..Default::default() };
+        let custom_size = RuleFeatures { has_custom_resolution: true, ..Default::default() };
+        let world = RuleFeatures { has_quick_plays_support: true, is_quick_play_singleplayer: true, ..Default::default() };
+        let server = RuleFeatures { has_quick_plays_support: true, is_quick_play_multiplayer: true, ..Default::default() };
+        let realm = RuleFeatures { has_quick_plays_support: true, is_quick_play_realms: true, ..Default::default() };
+
+        let cases = [
+            (&demo, &none, false),
+            (&demo, &demo_user, true),
+            (&resolution, &none, false),
+            (&resolution, &custom_size, true),
+            (&resolution, &demo_user, false),
+            (&singleplayer, &world, true),
+            (&singleplayer, &server, false),
+            (&multiplayer, &server, true),
+            (&multiplayer, &realm, false),
+            (&realms, &realm, true),
+            (&realms, &none, false),
+            (&quick_plays, &world, true),
+            (&quick_plays, &none, false),
+            (&unknown, &world, false),
+        ];
+        for (arg, features, expected) in cases {
+            assert_eq!(argument_allowed_on(arg, features, &LINUX), expected, "{:?} with {:?}", arg, features);
+        }
+    }
+
+    #[test]
+    fn feature_expected_false_matches_disabled_feature() {
+        let arg = argument(json!({
+            "rules": [{ "action": "allow", "features": { "is_demo_user": false } }],
+            "value": "--notDemo"
+        }));
+        assert!(argument_allowed_on(&arg, &RuleFeatures::default(), &LINUX));
+        assert!(!argument_allowed_on(&arg, &RuleFeatures { is_demo_user: true, ..Default::default() }, &LINUX));
+    }
+
+    #[test]
+    fn native_classifiers_substitute_arch() {
+        // tv.twitch:twitch-platform:6.5 and tv.twitch:twitch-external-platform:4.5 from 1.12.2
+        let twitch: Library = serde_json::from_value(json!({
+            "name": "tv.twitch:twitch-platform:6.5",
+            "natives": { "linux": "natives-linux", "osx": "natives-osx", "windows": "natives-windows-${arch}" }
+        })).unwrap();
+        let twitch_external: Library = serde_json::from_value(json!({
+            "name": "tv.twitch:twitch-external-platform:4.5",
+            "natives": { "windows": "natives-windows-${arch}" }
+        })).unwrap();
+
+        let cases = [
+            (&twitch, &WINDOWS_10, Some("natives-windows-64")),
+            (&twitch, &WINDOWS_7_X86, Some("natives-windows-32")),
+            (&twitch, &LINUX, Some("natives-linux")),
+            (&twitch, &MACOS, Some("natives-osx")),
+            (&twitch_external, &WINDOWS_10, Some("natives-windows-64")),
+            (&twitch_external, &LINUX, None),
+        ];
+        for (library, platform, expected) in cases {
+            assert_eq!(native_classifier_on(library, platform).as_deref(), expected, "{} on {:?}", library.name, platform);
+        }
+    }
+}
@@ -613,6 +613,8 @@ function App() {
             await invoke('install_forge', { instanceId: newInstance.id, loaderVersion: loader_version });
           } else if (loader === 'neoforge') {
             await invoke('install_neoforge', { instanceId: newInstance.id, loaderVersion: loader_version });
+          } else if (loader === 'quilt') {
+            await invoke('install_quilt', { instanceId: newInstance.id, loaderVersion: loader_version });
           }
         }
         
@@ -811,6 +813,26 @@ function App() {
               console.error('Failed to install NeoForge:', neoforgeError);
               showNotification(`Instance created but NeoForge installation failed: ${neoforgeError}`, 'error');
             }
+          } else if (modLoader === 'quilt') {
+            try {
+              const loaderVersions = modLoaderVersion ? [modLoaderVersion] : await invoke('get_loader_versions', {
+                loader: 'quilt',
+                gameVersion: versionId
+              });
+              const loaderVersion = modLoaderVersion || (loaderVersions && loaderVersions[0]);
+
+              if (loaderVersion) {
+                await invoke('install_quilt', {
+                  instanceId: newInstance.id,
+                  loaderVersion
+                });
+              } else {
+                showNotification(`No Quilt version found for ${versionId}`, 'error');
+              }
+            } catch (quiltError) {
+              console.error('Failed to install Quilt:', quiltError);
+              showNotification(`Instance created but Quilt installation failed: ${quiltError}`, 'error');
+            }
           }
         }
       }
@@ -484,7 +484,7 @@ function CreateInstance({ onClose, onCreate, isLoading, mode = 'page' }) {
                   <div className="mode-details">
                     <div className="mode-title">Custom Instance</div>
                     <div className="mode-description">
-                      Choose a specific Minecraft version and optionally install Fabric, Forge, or NeoForge.
+                      Choose a specific Minecraft version and optionally install Fabric, Quilt, Forge, or NeoForge.
                     </div>
                   </div>
                   <div className="mode-check">
@@ -750,6 +750,11 @@ function CreateInstance({ onClose, onCreate, isLoading, mode = 'page' }) {
                   name: 'Fabric',
                   icon: <img src="https://flintmc.net/brand/modification/81bae1feee32c1c794d63719ef123d0b.png" alt="Fabric" className="loader-logo-img fabric" />
                 },
+                {
+                  id: 'quilt',
+                  name: 'Quilt',
+                  icon: <img src="https://github.com/QuiltMC.png" alt="Quilt" className="loader-logo-img quilt" />
+                },
                 {
                   id: 'forge',
                   name: 'Forge',